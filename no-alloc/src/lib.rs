@@ -0,0 +1,41 @@
+//! Without the `alloc` feature, the builders must not link the `alloc` crate.
+//! This is a `no_std` staticlib without a global allocator, so it fails
+//! to link if any of the generated code uses `alloc`.
+#![no_std]
+
+use builders::{builder, Builder};
+
+#[derive(Builder)]
+pub struct Config {
+    pub id: u32,
+    #[builder(def = 8u8, range = 1..=64)]
+    pub threads: u8,
+    #[builder(optional = true)]
+    pub timeout: Option<u32>,
+}
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Limits {
+    #[builder(def = 10)]
+    pub max: u32,
+}
+
+pub const LIMITS: Limits = Limits::builder().max(3).build();
+
+#[builder]
+pub fn sum(a: u32, #[builder(def = 1u32)] b: u32) -> u32 {
+    a + b
+}
+
+#[no_mangle]
+pub extern "C" fn builders_no_alloc_check() -> u32 {
+    let Ok(config) = Config::builder().id(1u32).timeout(5u32).build() else { return 0 };
+    let Ok(sum) = sum_builder().a(config.id).call() else { return 0 };
+    sum + u32::from(config.threads) + config.timeout.unwrap_or_default() + LIMITS.max
+}
+
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
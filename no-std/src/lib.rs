@@ -0,0 +1,54 @@
+//! The code generated by every derive must only depend on `core` and `alloc`.
+//! This crate is `no_std`, so any use of `std` fails to compile.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+use alloc::collections::BTreeMap;
+use builders::*;
+
+#[derive(Builder, Getters, Setters, Constructor, AsBox, Debug, PartialEq)]
+#[builder(clone, debug, runtime, flattenable)]
+pub struct Config {
+    name: String,
+    #[builder(def = 8u8, range = 1..=64)]
+    threads: u8,
+    #[builder(optional = true)]
+    timeout: Option<u32>,
+    #[builder(vec = "arg", unique)]
+    args: Vec<String>,
+    #[builder(map = "env")]
+    env: BTreeMap<String, String>,
+}
+
+#[derive(Builder, AsBox, Debug)]
+#[builder(const)]
+pub struct Limits {
+    #[builder(def = 10)]
+    pub max: u32,
+}
+
+#[derive(Builder, Debug)]
+pub struct Job {
+    #[builder(start = true)]
+    pub id: u32,
+    #[builder(flatten = true)]
+    pub config: Config,
+}
+
+pub enum Item {
+    Wrapped(Wrapped),
+}
+
+#[derive(IntoEnum)]
+#[into_enum(enum_name = Item)]
+pub struct Wrapped;
+
+pub const LIMITS: Limits = Limits::builder().max(3).build();
+
+/// Sums the arguments
+#[builder]
+pub fn sum(a: u32, #[builder(def = 1u32)] b: u32) -> u32 {
+    a + b
+}
@@ -0,0 +1,20 @@
+use builders_no_std::*;
+
+#[test]
+fn builds() {
+    let builder = Config::builder()
+        .name("firmware")
+        .arg("-v")
+        .env("MODE", "release");
+    assert_eq!(builder.missing_fields(), Vec::<&str>::new());
+
+    let config = builder.clone().build_boxed().unwrap();
+    assert_eq!(*config.get_threads(), 8);
+    assert_eq!(config.get_args(), &["-v"]);
+
+    let job = Job::builder(1u32).name("job").build().unwrap();
+    assert_eq!(job.config.get_name(), "job");
+
+    assert_eq!(LIMITS.max, 3);
+    assert_eq!(sum_builder().a(2u32).call().unwrap(), 3);
+}
@@ -0,0 +1,53 @@
+//! Runtime traits implemented by the [builders](https://crates.io/crates/builders)
+//! derive macros.
+//!
+//! The `builders` crate is a proc-macro crate, so it can't export traits.
+//! Adding `#[builder(runtime)]` to a struct makes `#[derive(Builder)]`
+//! implement the traits of this crate for it, which allows writing generic
+//! code over any type with a derived builder.
+//!
+//! ```
+//! use builders::Builder;
+//! use builders_runtime::Buildable;
+//!
+//! #[derive(Builder)]
+//! #[builder(runtime)]
+//! struct Limits {
+//!     #[builder(def = 10u32)]
+//!     max_conns: u32,
+//! }
+//!
+//! fn fixture<T: Buildable>() -> T {
+//!     builders_runtime::Builder::build(T::builder()).ok().unwrap()
+//! }
+//!
+//! let limits: Limits = fixture();
+//! assert_eq!(limits.max_conns, 10);
+//! ```
+#![no_std]
+
+/// A type that can be built using a builder.
+///
+/// It's implemented for every struct deriving `Builder` with
+/// `#[builder(runtime)]`, as long as it doesn't have `start` fields.
+pub trait Buildable: Sized {
+    /// The builder of this type
+    type Builder: Builder<Output = Self>;
+
+    /// Returns a new builder
+    fn builder() -> Self::Builder;
+}
+
+/// A builder for [`Self::Output`](Builder::Output)
+pub trait Builder: Sized {
+    /// Type built by this builder
+    type Output;
+
+    /// Error returned by [`build`](Builder::build).
+    ///
+    /// For infallible builders, this is [`Infallible`](core::convert::Infallible).
+    type Error;
+
+    /// Builds the value
+    fn build(self) -> Result<Self::Output, Self::Error>;
+}
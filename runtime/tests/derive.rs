@@ -0,0 +1,53 @@
+// With #[builder(runtime)], the derived builders implement the traits
+// from this crate, which allows writing generic code over any type with
+// a derived builder.
+
+use builders::Builder;
+use builders_runtime::{Buildable, Builder as _};
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(runtime)]
+pub struct Limits {
+    #[builder(def = 10u32)]
+    max_conns: u32,
+}
+
+#[derive(Builder, Debug)]
+#[builder(runtime)]
+pub struct Command {
+    executable: String,
+}
+
+#[derive(Builder)]
+#[builder(runtime)]
+pub struct Request {
+    #[builder(start)]
+    url: String,
+}
+
+fn fixture<T: Buildable>() -> Result<T, <T::Builder as builders_runtime::Builder>::Error> {
+    T::builder().build()
+}
+
+fn build_all<B: builders_runtime::Builder>(builders: Vec<B>) -> Result<Vec<B::Output>, B::Error> {
+    builders.into_iter().map(|b| b.build()).collect()
+}
+
+#[test]
+fn buildable() {
+    let limits: Limits = fixture().unwrap();
+    assert_eq!(limits.max_conns, 10);
+    assert!(fixture::<Command>().is_err());
+}
+
+#[test]
+fn builder() {
+    let commands = build_all(vec![
+        Command::builder().executable("ls"),
+        Command::builder().executable("cd"),
+    ]).unwrap();
+    assert_eq!(commands[1].executable, "cd");
+
+    let request = builders_runtime::Builder::build(Request::builder("example.com")).unwrap();
+    assert_eq!(request.url, "example.com");
+}
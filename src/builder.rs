@@ -1,64 +1,115 @@
 use std::borrow::Cow;
 
-use proc_macro2::{TokenStream, TokenTree};
-use quote::quote;
-use syn::{parse_macro_input, parse_quote, Data, DataStruct, DeriveInput, Expr, Fields, FieldsNamed, Ident, Lit, Type};
+use proc_macro2::{Spacing, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{ext::IdentExt, parse::ParseStream, parse_macro_input, token, Token, parse_quote, punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, Data, DataStruct, DeriveInput, Expr, Field, Fields, FieldsNamed, Generics, Ident, Lit, Type, Visibility};
 
-use crate::util::{get_inner_ty, get_stripped_generics};
+use crate::util::{get_cfg_attrs, get_field_docs, get_inner_ty, get_stripped_generics, new_ident};
 
-fn try_find_attr(f: &[syn::Attribute], name: &str) -> Option<()> {
-    for attr in f {
-        let Ok(list) = attr.meta.require_list() else { continue };
+/// Attributes whose value is an expression. The value of any other attribute must be a literal.
+const EXPR_ATTRS: &[&str] = &["def", "default_with", "pre_build", "post_build", "range", "len"];
+
+/// Parses the comma separated entries of a `#[builder(...)]` attribute.
+/// Each entry is either `name`, `name = value` or `name(...)`.
+fn parse_attr_entries(input: ParseStream) -> syn::Result<Vec<Vec<TokenTree>>> {
+    let mut entries = Vec::new();
+    while !input.is_empty() {
+        let name = input.call(Ident::parse_any)?;
+        let mut entry = vec![TokenTree::Ident(name)];
+        if input.peek(Token![=]) {
+            let eq: Token![=] = input.parse()?;
+            let value: Expr = input.parse()?;
+            entry.extend(eq.into_token_stream());
+            entry.extend(value.into_token_stream());
+        } else if input.peek(token::Paren) {
+            entry.push(input.parse()?);
+        }
+        entries.push(entry);
+        if input.is_empty() { break }
+        input.parse::<Token![,]>()?;
+    }
+    Ok(entries)
+}
+
+fn builder_attrs(f: &[syn::Attribute]) -> impl Iterator<Item = &syn::MetaList> {
+    f.iter().filter_map(|attr| {
+        let list = attr.meta.require_list().ok()?;
         let segments = &list.path.segments;
-        if segments.len() != 1 || segments[0].ident != "builder" { continue }
-        let mut tokens = list.tokens.clone().into_iter();
-        match tokens.next().unwrap() {
-            TokenTree::Ident(ref i) => if i == name {
-                return Some(())
-            },
-            tt => panic!("Expected \"{name}\", found: {tt}"),
+        (segments.len() == 1 && segments[0].ident == "builder").then_some(list)
+    })
+}
+
+/// Returns the comma separated entries of every `#[builder(...)]` attribute.
+fn builder_attr_entries(f: &[syn::Attribute]) -> Vec<Vec<TokenTree>> {
+    builder_attrs(f).flat_map(|list| list.parse_args_with(parse_attr_entries).unwrap_or_default())
+                    .collect()
+}
+
+/// Checks the syntax of the `#[builder(...)]` attributes, so that
+/// the other functions can ignore the malformed entries.
+fn check_builder_attrs(f: &[syn::Attribute]) -> syn::Result<()> {
+    for list in builder_attrs(f) {
+        for entry in list.parse_args_with(parse_attr_entries)? {
+            let [TokenTree::Ident(name), TokenTree::Punct(_), value @ ..] = &entry[..] else { continue };
+            if EXPR_ATTRS.iter().any(|attr| name == attr) { continue }
+            let value: TokenStream = value.iter().cloned().collect();
+            syn::parse2::<Lit>(value.clone())
+                .map_err(|_| syn::Error::new_spanned(value, format!("Expected \"{name}\" to be a literal")))?;
         }
     }
-    None
+    Ok(())
+}
+
+fn try_find_attr(f: &[syn::Attribute], name: &str) -> Option<()> {
+    builder_attr_entries(f).into_iter().find_map(|entry| {
+        match &entry[..] {
+            [TokenTree::Ident(i)] if i == name => Some(()),
+            _ => None,
+        }
+    })
 }
 
 fn find_attr_nameval(f: &[syn::Attribute], name: &str) -> Result<TokenStream,proc_macro2::TokenStream> {
-    for attr in f {
-        let Ok(list) = attr.meta.require_list() else { continue };
-        let segments = &list.path.segments;
-        if segments.len() != 1 || segments[0].ident != "builder" { return Err(TokenStream::new()); }
-        let mut tokens = list.tokens.clone().into_iter();
-        match tokens.next().unwrap() {
-            TokenTree::Ident(ref i) => if i != name {
-                continue
-            },
-            tt => panic!("Expected \"{name}\", found: {tt}"),
+    for entry in builder_attr_entries(f) {
+        let mut tokens = entry.into_iter();
+        match tokens.next() {
+            Some(TokenTree::Ident(ref i)) if i == name => {},
+            _ => continue,
         }
-        match tokens.next().unwrap() {
-            TokenTree::Punct(ref p) => assert_eq!(p.as_char(), '='),
-            tt => panic!("Expected '=', found: {tt}"),
+        match tokens.next() {
+            Some(TokenTree::Punct(ref p)) if p.as_char() == '=' => {},
+            None => continue,
+            Some(_) => continue,
         }
-        return Ok(tokens.next().unwrap().into());
+        return Ok(tokens.collect());
     }
     Err(TokenStream::new())
 }
 
+/// Returns the contents of every `name(...)` entry
+fn find_attr_lists(f: &[syn::Attribute], name: &str) -> Vec<TokenStream> {
+    builder_attr_entries(f).into_iter().filter_map(|entry| {
+        match &entry[..] {
+            [TokenTree::Ident(i), TokenTree::Group(g)]
+                if i == name && g.delimiter() == proc_macro2::Delimiter::Parenthesis => Some(g.stream()),
+            _ => None,
+        }
+    }).collect()
+}
+
 fn find_attr_nameval_lit(f: &[syn::Attribute], name: &str) -> Result<Lit,proc_macro2::TokenStream> {
     let token = find_attr_nameval(f, name)?;
-    let lit: Lit = parse_quote!(#token);
-    Ok(lit)
+    syn::parse2(token).map_err(|err| err.to_compile_error())
 }
 
 fn find_attr_nameval_expr(f: &[syn::Attribute], name: &str) -> Result<Expr,proc_macro2::TokenStream> {
     let token = find_attr_nameval(f, name)?;
-    let lit: Expr = parse_quote!(#token);
-    Ok(lit)
+    syn::parse2(token).map_err(|err| err.to_compile_error())
 }
 
 fn find_attr_nameval_bool(f: &[syn::Attribute], name: &str) -> Result<bool,proc_macro2::TokenStream> {
     if try_find_attr(f, name).is_some() { return Ok(true) }
-    let token = find_attr_nameval(f, name)?;
-    let lit: Lit = parse_quote!(#token);
+    let lit = find_attr_nameval_lit(f, name)?;
     let Lit::Bool(b) = lit else { return Err(quote! { compile_error!("Expected boolean attribute"); })};
     Ok(b.value)
 }
@@ -76,11 +127,490 @@ fn is_each(f: &syn::Field) -> bool {
     || matches!(find_attr_nameval_lit(&f.attrs, "map"), Ok(Lit::Str(_)))
 }
 
+/// Start fields are passed as arguments to the `builder()` function
+fn is_start(f: &syn::Field) -> bool {
+    find_attr_nameval_bool(&f.attrs, "start").unwrap_or_default()
+}
+
+/// Name of the field, without the `r#` prefix
+fn field_name_str(f: &syn::Field) -> String {
+    f.ident.as_ref().unwrap().unraw().to_string()
+}
+
+/// The `#[cfg]` attributes of the field, to be copied onto the code generated for it
+fn field_cfg(f: &syn::Field) -> TokenStream {
+    let cfg = get_cfg_attrs(&f.attrs);
+    quote!( #( #cfg )* )
+}
+
+/// Sensitive fields are printed as `<redacted>` by the generated Debug impls
+fn is_sensitive(f: &syn::Field) -> bool {
+    find_attr_nameval_bool(&f.attrs, "sensitive").unwrap_or_default()
+}
+
+/// Flattened fields expose the setters of their builder on the outer builder
+fn is_flatten(f: &syn::Field) -> bool {
+    find_attr_nameval_bool(&f.attrs, "flatten").unwrap_or_default()
+}
+
+/// The error returned by build() for the message `msg`.
+///
+/// Builders with flattened fields prefix the errors of the inner builders,
+/// so their errors are `Cow<'static, str>` instead of `&'static str`.
+fn error_value(msg: &str, owned: bool) -> TokenStream {
+    if owned {
+        quote!( alloc::borrow::Cow::Borrowed(#msg) )
+    } else {
+        quote!( #msg )
+    }
+}
+
+/// Returns the type with `suffix` appended to it's name, keeping
+/// it's path and generic arguments.
+/// For example, `config::Net<T>` becomes `config::NetBuilder<T>`
+fn suffixed_type(ty: &Type, suffix: &str) -> syn::Result<Type> {
+    let Type::Path(path) = ty else {
+        return Err(syn::Error::new(ty.span(), "Expected a type deriving Builder"));
+    };
+    let mut path = path.clone();
+    let last = path.path.segments.last_mut().unwrap();
+    last.ident = new_ident(&format!("{}{suffix}", last.ident.unraw()), last.ident.span());
+    Ok(Type::Path(path))
+}
+
+/// A group of fields with a constraint on how many of them can be set
+struct Group<'a> {
+    name: String,
+    fields: Vec<&'a Field>,
+    /// At most one of the fields can be set
+    exclusive: bool,
+    /// At least one of the fields must be set
+    required: bool,
+}
+
+fn field_groups(fields: &Punctuated<Field, Comma>) -> syn::Result<Vec<Group<'_>>> {
+    let mut groups: Vec<Group> = Vec::new();
+    for f in fields.iter().filter(|f| !is_disabled(f)) {
+        let Ok(lit) = find_attr_nameval_lit(&f.attrs, "group") else { continue };
+        let Lit::Str(name) = lit else {
+            return Err(syn::Error::new(lit.span(), "Expected the group name to be a string"));
+        };
+        let name = name.value();
+        let pos = match groups.iter().position(|g| g.name == name) {
+            Some(pos) => pos,
+            None => {
+                groups.push(Group { name, fields: Vec::new(), exclusive: false, required: false });
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[pos];
+        group.fields.push(f);
+        group.exclusive |= try_find_attr(&f.attrs, "exclusive").is_some();
+        group.required |= try_find_attr(&f.attrs, "required").is_some();
+    }
+    if let Some(g) = groups.iter().find(|g| !g.exclusive && !g.required) {
+        return Err(syn::Error::new(g.fields[0].span(), format!("Group \"{}\" must be exclusive, required, or both", g.name)));
+    }
+    Ok(groups)
+}
+
+/// Checks for the constraints of the groups
+fn group_checks(groups: &[Group], owned: bool) -> Vec<TokenStream> {
+    groups.iter().map(|group| {
+        let names = group.fields.iter()
+                         .map(|f| field_name_str(f))
+                         .collect::<Vec<_>>()
+                         .join(", ");
+        let is_set = group.fields.iter().map(|f| {
+            let cfg = field_cfg(f);
+            let is_set = is_set_expr(f);
+            quote!( #cfg #is_set )
+        });
+        let mut check = quote! {
+            let count = [ #( #is_set ),* ].iter().filter(|set| **set).count();
+        };
+        if group.exclusive {
+            let msg = error_value(&format!("Only one of the fields of group \"{}\" can be set: {names}", group.name), owned);
+            check.extend(quote! {
+                if count > 1 {
+                    return ::core::result::Result::Err(#msg);
+                }
+            });
+        }
+        if group.required {
+            let msg = error_value(&format!("One of the fields of group \"{}\" must be set: {names}", group.name), owned);
+            check.extend(quote! {
+                if count == 0 {
+                    return ::core::result::Result::Err(#msg);
+                }
+            });
+        }
+        quote! {{ #check }}
+    }).collect()
+}
+
+/// Checks for the `requires` and `required_if` attributes
+fn dependency_checks(fields: &Punctuated<Field, Comma>, owned: bool) -> syn::Result<Vec<TokenStream>> {
+    let find_field = |f: &Field, attr: &str| -> syn::Result<Option<&Field>> {
+        let Ok(lit) = find_attr_nameval_lit(&f.attrs, attr) else { return Ok(None) };
+        let Lit::Str(name) = &lit else {
+            return Err(syn::Error::new(lit.span(), format!("Expected \"{attr}\" to be a string")));
+        };
+        fields.iter()
+              .find(|other| !is_disabled(other) && field_name_str(other) == name.value())
+              .map(Some)
+              .ok_or_else(|| syn::Error::new(name.span(), format!("Unknown field \"{}\"", name.value())))
+    };
+
+    let mut checks = Vec::new();
+    for f in fields.iter().filter(|f| !is_disabled(f)) {
+        let fname = field_name_str(f);
+        let is_set = is_set_expr(f);
+        let cfg = field_cfg(f);
+        if let Some(other) = find_field(f, "requires")? {
+            let other_set = is_set_expr(other);
+            let other_cfg = field_cfg(other);
+            let msg = error_value(&format!("{fname} requires {} to be set", field_name_str(other)), owned);
+            checks.push(quote! {
+                #cfg #other_cfg
+                if #is_set && !(#other_set) {
+                    return ::core::result::Result::Err(#msg);
+                }
+            });
+        }
+        if let Some(other) = find_field(f, "required_if")? {
+            let other_set = is_set_expr(other);
+            let other_cfg = field_cfg(other);
+            let msg = error_value(&format!("{fname} is required when {} is set", field_name_str(other)), owned);
+            checks.push(quote! {
+                #cfg #other_cfg
+                if #other_set && !(#is_set) {
+                    return ::core::result::Result::Err(#msg);
+                }
+            });
+        }
+    }
+    Ok(checks)
+}
+
+/// Checks for the `range` and `len` attributes, on the final value of the fields
+fn value_checks(fields: &Punctuated<Field, Comma>, owned: bool) -> Vec<TokenStream> {
+    let mut checks = Vec::new();
+    for f in fields.iter() {
+        let field_name = &f.ident;
+        let fname = field_name_str(f);
+        let mut check = |attr: &str, value: TokenStream, what: &str| {
+            let Ok(range) = find_attr_nameval(&f.attrs, attr) else { return };
+            let range_str = range.to_string().replace(' ', "");
+            let msg = error_value(&format!("{fname} {what} must be in the range {range_str}"), owned);
+            let cond = quote! {
+                if !(#range).contains(&#value) {
+                    return ::core::result::Result::Err(#msg);
+                }
+            };
+            let cfg = field_cfg(f);
+            checks.push(if is_optional(f) {
+                quote! {
+                    #cfg
+                    if let ::core::option::Option::Some(#field_name) = &#field_name {
+                        #cond
+                    }
+                }
+            } else {
+                quote!( #cfg #cond )
+            });
+        };
+        let value = if is_optional(f) { quote!(*#field_name) } else { quote!(#field_name) };
+        check("range", value, "value");
+        check("len", quote!(#field_name.len()), "length");
+    }
+    checks
+}
+
+/// What to do when a `vec` or `map` field receives a duplicated entry
+#[derive(Clone, Copy, PartialEq)]
+enum DuplicatePolicy {
+    /// Replace the old entry with the new one
+    Replace,
+    /// Ignore the new entry
+    KeepFirst,
+    /// Make build() fail
+    Error,
+}
+
+fn duplicate_policy(f: &syn::Field) -> syn::Result<Option<DuplicatePolicy>> {
+    if find_attr_nameval_bool(&f.attrs, "unique").unwrap_or_default() {
+        return Ok(Some(DuplicatePolicy::Error));
+    }
+    let Ok(lit) = find_attr_nameval_lit(&f.attrs, "on_duplicate") else { return Ok(None) };
+    match &lit {
+        Lit::Str(s) if s.value() == "replace" => Ok(Some(DuplicatePolicy::Replace)),
+        Lit::Str(s) if s.value() == "keep_first" => Ok(Some(DuplicatePolicy::KeepFirst)),
+        Lit::Str(s) if s.value() == "error" => Ok(Some(DuplicatePolicy::Error)),
+        _ => Err(syn::Error::new(lit.span(), "Expected \"replace\", \"keep_first\" or \"error\"")),
+    }
+}
+
+/// Name of the builder field that records if a duplicated entry was inserted
+fn duplicate_flag(f: &syn::Field) -> Option<Ident> {
+    if duplicate_policy(f).ok()?? != DuplicatePolicy::Error { return None }
+    let fname = f.ident.as_ref().unwrap();
+    Some(new_ident(&format!("__duplicate_{}", fname.unraw()), fname.span()))
+}
+
+/// Code that inserts `arg` (and `val` for maps) into a `vec` or `map` field
+fn insert_entry(f: &syn::Field, arg: &Ident) -> TokenStream {
+    let field_name = &f.ident;
+    let policy = duplicate_policy(f).ok().flatten();
+    let flag = duplicate_flag(f);
+    if find_attr_nameval(&f.attrs, "map").is_ok() {
+        match policy {
+            Some(DuplicatePolicy::Error) => quote! {
+                if self.#field_name.insert(#arg.into(), val.into()).is_some() {
+                    self.#flag = true;
+                }
+            },
+            Some(DuplicatePolicy::KeepFirst) => quote! {
+                let #arg = #arg.into();
+                if !self.#field_name.contains_key(&#arg) {
+                    self.#field_name.insert(#arg, val.into());
+                }
+            },
+            Some(DuplicatePolicy::Replace) | None => quote! {
+                self.#field_name.insert(#arg.into(),val.into());
+            },
+        }
+    } else {
+        match policy {
+            Some(DuplicatePolicy::Error) => quote! {
+                let #arg = #arg.into();
+                if self.#field_name.contains(&#arg) {
+                    self.#flag = true;
+                }
+                self.#field_name.push(#arg);
+            },
+            Some(DuplicatePolicy::KeepFirst) => quote! {
+                let #arg = #arg.into();
+                if !self.#field_name.contains(&#arg) {
+                    self.#field_name.push(#arg);
+                }
+            },
+            Some(DuplicatePolicy::Replace) => quote! {
+                let #arg = #arg.into();
+                match self.#field_name.iter().position(|e| *e == #arg) {
+                    ::core::option::Option::Some(i) => self.#field_name[i] = #arg,
+                    ::core::option::Option::None => self.#field_name.push(#arg),
+                }
+            },
+            None => quote! {
+                self.#field_name.push(#arg.into());
+            },
+        }
+    }
+}
+
+/// Makes build() fail if a duplicated entry was inserted into
+/// a field with the `error` policy
+fn duplicate_checks(fields: &Punctuated<Field, Comma>, owned: bool) -> syn::Result<Vec<TokenStream>> {
+    let mut checks = Vec::new();
+    for f in fields {
+        if duplicate_policy(f)?.is_some() && !is_each(f) {
+            return Err(syn::Error::new(f.span(), "unique and on_duplicate can only be used on vec and map fields"));
+        }
+        if let Some(flag) = duplicate_flag(f) {
+            let msg = error_value(&format!("Duplicate entry in {}", field_name_str(f)), owned);
+            let cfg = field_cfg(f);
+            checks.push(quote! {
+                #cfg
+                if self.#flag {
+                    return ::core::result::Result::Err(#msg);
+                }
+            });
+        }
+    }
+    Ok(checks)
+}
+
+/// The `#[inline]` attribute for the generated methods, from
+/// the `inline = "always" | "never"` attribute
+fn inline_attr(attrs: &[Attribute]) -> syn::Result<TokenStream> {
+    let Ok(lit) = find_attr_nameval_lit(attrs, "inline") else { return Ok(quote!()) };
+    match &lit {
+        Lit::Str(s) if s.value() == "always" => Ok(quote!( #[inline(always)] )),
+        Lit::Str(s) if s.value() == "never" => Ok(quote!( #[inline(never)] )),
+        _ => Err(syn::Error::new(lit.span(), "Expected \"always\" or \"never\"")),
+    }
+}
+
+/// Returns the name of the owned and `set_` setters of the field,
+/// and their parameters and arguments.
+fn setter_signature(f: &syn::Field) -> (Ident, Ident, TokenStream, TokenStream) {
+    let field_name = f.ident.as_ref().unwrap();
+    let set = |name: &str, span| (new_ident(name, span), new_ident(&format!("set_{name}"), span));
+    if let Ok(Lit::Str(lit)) = find_attr_nameval_lit(&f.attrs, "vec") {
+        let (name, set_name) = set(&lit.value(), lit.span());
+        let inner = get_inner_ty(&f.ty).and_then(|tys| tys.first().copied());
+        (name.clone(), set_name, quote! { #name: impl ::core::convert::Into<#inner> }, quote! { #name })
+    } else if let Ok(Lit::Str(lit)) = find_attr_nameval_lit(&f.attrs, "map") {
+        let (name, set_name) = set(&lit.value(), lit.span());
+        let tys = get_inner_ty(&f.ty).unwrap_or_default();
+        let (first, second) = (tys.first(), tys.get(1));
+        (name.clone(), set_name,
+         quote! { #name: impl ::core::convert::Into<#first>, val: impl ::core::convert::Into<#second> },
+         quote! { #name, val })
+    } else {
+        let (name, set_name) = set(&field_name.unraw().to_string(), field_name.span());
+        let ty = if is_optional(f) {
+            get_inner_ty(&f.ty).map(|l| l[0]).unwrap_or(&f.ty)
+        } else {
+            &f.ty
+        };
+        (name.clone(), set_name, quote! { #name: impl ::core::convert::Into<#ty> }, quote! { #name })
+    }
+}
+
+/// A required field must be set before calling `build()`
+fn is_required(f: &syn::Field) -> bool {
+    !is_disabled(f) && !is_optional(f) && !is_each(f) && !is_start(f) && !is_flatten(f)
+    && find_attr_nameval(&f.attrs, "def").is_err()
+    && find_attr_nameval(&f.attrs, "default_with").is_err()
+}
+
+/// Expression that checks if the field has been set on the builder
+fn is_set_expr(f: &syn::Field) -> TokenStream {
+    let field_name = &f.ident;
+    if is_start(f) {
+        quote! { true }
+    } else if is_each(f) {
+        quote! { !self.#field_name.is_empty() }
+    } else {
+        quote! { self.#field_name.is_some() }
+    }
+}
+
+/// Value of the field on a newly created builder
+fn empty_value(f: &syn::Field) -> TokenStream {
+    let field_name = &f.ident;
+    if is_start(f) {
+        quote! { #field_name.into() }
+    } else if is_each(f) || is_flatten(f) {
+        quote! { ::core::default::Default::default() }
+    } else {
+        quote! { ::core::option::Option::None }
+    }
+}
+
+fn def_dependencies(f: &syn::Field, fields: &Punctuated<Field, Comma>) -> Vec<usize> {
+    fn is_path_sep(a: Option<&TokenTree>, b: Option<&TokenTree>) -> bool {
+        matches!((a, b), (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b)))
+                         if a.as_char() == ':' && a.spacing() == Spacing::Joint && b.as_char() == ':')
+    }
+    /// Only bare identifiers count as field references. Method
+    /// calls (`.ident`), path segments (`a::ident`, `ident::a`),
+    /// function calls (`ident(..)`) and macros (`ident!`) are skipped.
+    fn collect(tokens: TokenStream, fields: &Punctuated<Field, Comma>, deps: &mut Vec<usize>) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        for (idx, tt) in tokens.iter().enumerate() {
+            match tt {
+                TokenTree::Ident(i) => {
+                    let after_dot = idx > 0 && matches!(&tokens[idx - 1], TokenTree::Punct(p) if p.as_char() == '.');
+                    let after_sep = idx > 1 && is_path_sep(tokens.get(idx - 2), tokens.get(idx - 1));
+                    let before_sep = is_path_sep(tokens.get(idx + 1), tokens.get(idx + 2));
+                    let is_call = match tokens.get(idx + 1) {
+                        Some(TokenTree::Group(g)) => g.delimiter() == proc_macro2::Delimiter::Parenthesis,
+                        Some(TokenTree::Punct(p)) => p.as_char() == '!' && p.spacing() == Spacing::Alone,
+                        _ => false,
+                    };
+                    if after_dot || after_sep || before_sep || is_call { continue }
+                    if let Some(pos) = fields.iter().position(|f| f.ident.as_ref() == Some(i)) {
+                        if !deps.contains(&pos) { deps.push(pos) }
+                    }
+                },
+                TokenTree::Group(g) => collect(g.stream(), fields, deps),
+                _ => {}
+            }
+        }
+    }
+    let mut deps = Vec::new();
+    if is_disabled(f) || !is_optional(f) {
+        if let Ok(tokens) = find_attr_nameval(&f.attrs, "def") {
+            collect(tokens, fields, &mut deps);
+        }
+    }
+    /* The field itself is not in scope inside it's own def expression */
+    deps.retain(|&dep| !std::ptr::eq(&fields[dep], f));
+    deps
+}
+
+/// Sorts the fields so that every field comes after the
+/// ones referenced by it's `def` expression.
+fn sort_by_dependencies(fields: &Punctuated<Field, Comma>) -> syn::Result<Vec<&Field>> {
+    fn visit<'a>(i: usize, fields: &'a Punctuated<Field, Comma>, state: &mut [u8],
+                 path: &mut Vec<usize>, sorted: &mut Vec<&'a Field>) -> syn::Result<()> {
+        match state[i] {
+            2 => return Ok(()),
+            1 => {
+                let start = path.iter().position(|&p| p == i).unwrap_or_default();
+                let cycle = path[start..].iter().chain([&i])
+                                .map(|&p| field_name_str(&fields[p]))
+                                .collect::<Vec<_>>()
+                                .join(" -> ");
+                return Err(syn::Error::new(fields[i].span(),
+                        format!("Cycle detected in the default values: {cycle}")));
+            },
+            _ => {}
+        }
+        state[i] = 1;
+        path.push(i);
+        for dep in def_dependencies(&fields[i], fields) {
+            visit(dep, fields, state, path, sorted)?;
+        }
+        path.pop();
+        state[i] = 2;
+        sorted.push(&fields[i]);
+        Ok(())
+    }
+
+    let mut state = vec![0; fields.len()];
+    let mut sorted = Vec::with_capacity(fields.len());
+    for i in 0..fields.len() {
+        visit(i, fields, &mut state, &mut Vec::new(), &mut sorted)?;
+    }
+    Ok(sorted)
+}
+
+/// What the generated builder produces
+pub (crate) enum Target<'a> {
+    /// A struct deriving Builder
+    Struct(&'a Ident),
+    /// A function marked with the #[builder] attribute.
+    Fn {
+        /// Path used to call the function
+        path: TokenStream,
+        /// Return type of the function
+        output: TokenStream,
+    },
+}
+
+pub (crate) struct BuilderInput<'a> {
+    pub vis: &'a Visibility,
+    pub attrs: &'a [Attribute],
+    pub generics: &'a Generics,
+    pub fields: &'a Punctuated<Field, Comma>,
+    pub builder_name: Ident,
+    /// Name of the function that creates the builder
+    pub entry_name: Ident,
+    /// Generic parameters of the function that creates the builder.
+    /// None if they are already in scope.
+    pub entry_generics: Option<Generics>,
+    pub target: Target<'a>,
+}
+
 pub (crate) fn builder_derive_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let ident = &ast.ident;
-    let builder_name = format!("{ident}Builder");
-    let builder_name = Ident::new(&builder_name, ident.span());
+    let builder_name = format!("{}Builder", ident.unraw());
+    let builder_name = new_ident(&builder_name, ident.span());
 
     let fields=
     if let Data::Struct(DataStruct {
@@ -91,31 +621,113 @@ pub (crate) fn builder_derive_impl(input: proc_macro::TokenStream) -> proc_macro
     } else {
         unimplemented!()
     };
+
+    let input = BuilderInput {
+        vis: &ast.vis,
+        attrs: &ast.attrs,
+        generics: &ast.generics,
+        fields,
+        builder_name,
+        entry_name: Ident::new("builder", ident.span()),
+        entry_generics: None,
+        target: Target::Struct(ident),
+    };
+    let (items, entry) = match gen_builder(&input) {
+        Ok(gen) => gen,
+        Err(err) => return err.into_compile_error().into(),
+    };
+
+    let generics = &ast.generics;
+    let wher = &generics.where_clause;
+    let stripped_generics = get_stripped_generics(generics, false);
+    let generics_no_defaults = get_stripped_generics(generics, true);
+
+    quote! {
+        #items
+
+        impl #generics_no_defaults #ident #stripped_generics #wher {
+            #entry
+        }
+    }.into()
+}
+
+/// Generates the builder struct with all it's methods, and the
+/// function that creates a new builder.
+pub (crate) fn gen_builder(input: &BuilderInput) -> syn::Result<(TokenStream, TokenStream)> {
+    let BuilderInput { fields, builder_name, entry_name, .. } = input;
+    check_builder_attrs(input.attrs)?;
+    for f in fields.iter() {
+        check_builder_attrs(&f.attrs)?;
+    }
+    let inline = inline_attr(input.attrs)?;
+    let is_const = find_attr_nameval_bool(input.attrs, "const").is_ok_and(|v| v);
+    let constness = if is_const { quote!(const) } else { quote!() };
+    let owned_attrs = quote! { #[must_use] #inline };
+    let alloc = cfg!(feature = "alloc");
+    if let (false, Some(f)) = (alloc, fields.iter().find(|f| is_flatten(f))) {
+        return Err(syn::Error::new(f.span(), "flattened fields require the \"alloc\" feature of builders"));
+    }
+    let owned_errors = fields.iter().any(is_flatten);
+    let error_ty = if owned_errors {
+        quote!( alloc::borrow::Cow<'static, str> )
+    } else {
+        quote!( &'static str )
+    };
     let builder_fields = fields.iter().filter(|f| !is_disabled(f)).map(|f| {
         let name = &f.ident;
         let ty = &f.ty;
-        if is_optional(f) || is_each(f) {
-            quote!( #name: #ty )
+        let field_attrs = find_attr_lists(&f.attrs, "field_attr");
+        let cfg = field_cfg(f);
+        if is_flatten(f) {
+            let builder_ty = suffixed_type(ty, "Builder").unwrap();
+            quote!( #cfg #( #[#field_attrs] )* #name: #builder_ty )
+        } else if is_optional(f) || is_each(f) || (is_start(f) && !is_const) {
+            quote!( #cfg #( #[#field_attrs] )* #name: #ty )
         } else {
-            quote!( #name: ::core::option::Option<#ty> )
+            /* const builders keep the start fields in an Option too, so that build() can take them out */
+            quote!( #cfg #( #[#field_attrs] )* #name: ::core::option::Option<#ty> )
         }
     });
-    let builder_methods = fields.iter().filter(|f| !is_disabled(f)).map(|f| {
+    let duplicate_flags = fields.iter().filter(|f| !is_disabled(f))
+                                .filter_map(|f| Some((field_cfg(f), duplicate_flag(f)?)))
+                                .collect::<Vec<_>>();
+    /* The generic parameters of a function may not be used by any of it's
+     * arguments, so the builder marks them as used with a PhantomData */
+    let marker = match input.target {
+        Target::Fn { .. } if input.generics.type_params().next().is_some() || input.generics.lifetimes().next().is_some() => {
+            let tys = input.generics.type_params().map(|tp| &tp.ident);
+            let lts = input.generics.lifetimes().map(|lt| &lt.lifetime);
+            Some(quote! {
+                ::core::marker::PhantomData<fn() -> ( #( ::core::marker::PhantomData<#tys>, )* #( &#lts (), )* )>
+            })
+        },
+        _ => None,
+    };
+    let marker_name = quote!(__builders_marker);
+    let builder_fields = builder_fields.chain(duplicate_flags.iter().map(|(cfg, flag)| quote!( #cfg #flag: bool )))
+                                       .chain(marker.iter().map(|ty| quote!( #marker_name: #ty )));
+    let builder_methods = fields.iter().filter(|f| !is_disabled(f) && !is_flatten(f)).map(|f| {
         let field_name = &f.ident;
         match find_attr_nameval_lit(&f.attrs, "vec") {
             Ok(Lit::Str(lit)) => {
-                let arg = Ident::new(&lit.value(), lit.span());
-                let set_arg = Ident::new(&format!("set_{}", &lit.value()), lit.span());
+                let arg = new_ident(&lit.value(), lit.span());
+                let set_arg = new_ident(&format!("set_{}", &lit.value()), lit.span());
                 let tys = get_inner_ty(&f.ty).expect("Expected at least one generic argument");
                 let inner = tys.first();
+                let insert = insert_entry(f, &arg);
+                let docs = get_field_docs(f, &format!("Adds an element to `{}`", field_name_str(f)));
                 quote! {
+                    #docs
+                    #owned_attrs
                     pub fn #arg(mut self, #arg: impl ::core::convert::Into<#inner>) -> Self {
-                        self.#field_name.push(#arg.into());
+                        #insert
                         self
                     }
 
+                    #docs
+                    #inline
                     pub fn #set_arg(&mut self, #arg: impl ::core::convert::Into<#inner>) -> &mut Self {
-                        self.#field_name.push(#arg.into());
+                        #insert
                         self
                     }
                 }
@@ -128,17 +740,23 @@ pub (crate) fn builder_derive_impl(input: proc_macro::TokenStream) -> proc_macro
             Err(_) => {
                 match find_attr_nameval_lit(&f.attrs, "map") {
                     Ok(Lit::Str(lit)) => {
-                        let arg = Ident::new(&lit.value(), lit.span());
-                        let set_arg = Ident::new(&format!("set_{}", &lit.value()), lit.span());
+                        let arg = new_ident(&lit.value(), lit.span());
+                        let set_arg = new_ident(&format!("set_{}", &lit.value()), lit.span());
                         let [first, second] = get_inner_ty(&f.ty).expect("Expected at least one generic argument")[..2] else { panic!() };
+                        let insert = insert_entry(f, &arg);
+                        let docs = get_field_docs(f, &format!("Inserts an entry into `{}`", field_name_str(f)));
                         quote! {
+                            #docs
+                            #owned_attrs
                             pub fn #arg(mut self, #arg: impl ::core::convert::Into<#first>, val: impl ::core::convert::Into<#second>) -> Self {
-                                self.#field_name.insert(#arg.into(),val.into());
+                                #insert
                                 self
                             }
 
+                            #docs
+                            #inline
                             pub fn #set_arg(&mut self, #arg: impl ::core::convert::Into<#first>, val: impl ::core::convert::Into<#second>) -> &mut Self {
-                                self.#field_name.insert(#arg.into(),val.into());
+                                #insert
                                 self
                             }
                         }
@@ -157,16 +775,96 @@ pub (crate) fn builder_derive_impl(input: proc_macro::TokenStream) -> proc_macro
                             Cow::Borrowed(&f.ty)
                         };
                         let fname = field_name.as_ref().unwrap();
-                        let set_name = Ident::new(&format!("set_{}", &fname.to_string()), fname.span());
+                        let span = fname.span();
+                        let fname = fname.unraw();
+                        let set_name = new_ident(&format!("set_{fname}"), span);
+                        let maybe_name = new_ident(&format!("maybe_{fname}"), span);
+                        let if_name = new_ident(&format!("{fname}_if"), span);
+                        let docs = get_field_docs(f, &format!("Sets `{fname}`"));
+                        let maybe_doc = format!("Sets `{fname}` if the value is `Some`");
+                        let if_doc = format!("Sets `{fname}` if `cond` is true");
+                        /* const fns can't call Into::into, nor drop the old value of the field */
+                        let (param_ty, assign) = if is_const {
+                            (quote!( #ty ), quote! {
+                                let _ = ::core::mem::ManuallyDrop::new(self.#field_name.replace(#field_name));
+                            })
+                        } else if is_start(f) {
+                            (quote!( impl ::core::convert::Into<#ty> ), quote! { self.#field_name = #field_name.into(); })
+                        } else {
+                            (quote!( impl ::core::convert::Into<#ty> ), quote! {
+                                self.#field_name = ::core::option::Option::Some(#field_name.into());
+                            })
+                        };
+                        if is_start(f) {
+                            return quote! {
+                                #docs
+                                #owned_attrs
+                                pub #constness fn #field_name(mut self, #field_name: #param_ty) -> Self {
+                                    #assign
+                                    self
+                                }
+
+                                #docs
+                                #inline
+                                pub #constness fn #set_name(&mut self, #field_name: #param_ty) -> &mut Self {
+                                    #assign
+                                    self
+                                }
+                            }
+                        }
+                        let (maybe_assign, if_assign) = if is_const {
+                            (quote! {
+                                if #field_name.is_some() {
+                                    let #field_name = #field_name.unwrap();
+                                    #assign
+                                } else {
+                                    let _ = ::core::mem::ManuallyDrop::new(#field_name);
+                                }
+                            }, quote! {
+                                if cond {
+                                    #assign
+                                } else {
+                                    let _ = ::core::mem::ManuallyDrop::new(#field_name);
+                                }
+                            })
+                        } else {
+                            (quote! {
+                                if let ::core::option::Option::Some(#field_name) = #field_name {
+                                    #assign
+                                }
+                            }, quote! {
+                                if cond {
+                                    #assign
+                                }
+                            })
+                        };
                         quote! {
                             #err
-                            pub fn #field_name(mut self, #field_name: impl ::core::convert::Into<#ty>) -> Self {
-                                self.#field_name = ::core::option::Option::Some(#field_name.into());
+                            #docs
+                            #owned_attrs
+                            pub #constness fn #field_name(mut self, #field_name: #param_ty) -> Self {
+                                #assign
                                 self
                             }
 
-                            pub fn #set_name(&mut self, #field_name: impl ::core::convert::Into<#ty>) -> &mut Self {
-                                self.#field_name = ::core::option::Option::Some(#field_name.into());
+                            #docs
+                            #inline
+                            pub #constness fn #set_name(&mut self, #field_name: #param_ty) -> &mut Self {
+                                #assign
+                                self
+                            }
+
+                            #[doc = #maybe_doc]
+                            #owned_attrs
+                            pub #constness fn #maybe_name(mut self, #field_name: ::core::option::Option<#param_ty>) -> Self {
+                                #maybe_assign
+                                self
+                            }
+
+                            #[doc = #if_doc]
+                            #owned_attrs
+                            pub #constness fn #if_name(mut self, cond: bool, #field_name: #param_ty) -> Self {
+                                #if_assign
                                 self
                             }
                         }
@@ -175,81 +873,324 @@ pub (crate) fn builder_derive_impl(input: proc_macro::TokenStream) -> proc_macro
             }
         }
     });
-    let empty_fields = fields.iter().filter(|f| !is_disabled(f)).map(|f| {
+    let introspection_methods = fields.iter().filter(|f| !is_disabled(f) && !is_flatten(f)).map(|f| {
         let field_name = &f.ident;
-        if is_each(f) {
-            return quote! { #field_name : ::core::default::Default::default() };
+        let fname = field_name.as_ref().unwrap();
+        let span = fname.span();
+        let fname = fname.unraw();
+        let is_set_name = new_ident(&format!("is_{fname}_set"), span);
+        let get_name = new_ident(&format!("get_{fname}"), span);
+        let unset_name = new_ident(&format!("unset_{fname}"), span);
+        let clear_name = new_ident(&format!("clear_{fname}"), span);
+        let is_set = is_set_expr(f);
+        let empty = empty_value(f);
+        let (ty, get) = if is_each(f) {
+            (&f.ty, quote! { if #is_set { ::core::option::Option::Some(&self.#field_name) } else { ::core::option::Option::None } })
+        } else if is_start(f) && !is_const {
+            (&f.ty, quote! { ::core::option::Option::Some(&self.#field_name) })
+        } else if is_optional(f) {
+            (get_inner_ty(&f.ty).map(|l| l[0]).unwrap_or(&f.ty), quote! { self.#field_name.as_ref() })
+        } else {
+            (&f.ty, quote! { self.#field_name.as_ref() })
+        };
+        let is_set_doc = format!("Returns true if `{fname}` has been set");
+        let get_doc = format!("Returns the value of `{fname}`, if it has been set");
+        let unset_doc = format!("Resets `{fname}` to it's initial value");
+        let introspection = quote! {
+            #[doc = #is_set_doc]
+            #inline
+            pub fn #is_set_name(&self) -> bool {
+                #is_set
+            }
+
+            #[doc = #get_doc]
+            #inline
+            pub fn #get_name(&self) -> ::core::option::Option<&#ty> {
+                #get
+            }
+        };
+        if is_start(f) {
+            return introspection;
+        }
+        let reset_flag = duplicate_flag(f).map(|flag| quote! { self.#flag = false; });
+        quote! {
+            #introspection
+
+            #[doc = #unset_doc]
+            #owned_attrs
+            pub fn #unset_name(mut self) -> Self {
+                self.#field_name = #empty;
+                #reset_flag
+                self
+            }
+
+            #[doc = #unset_doc]
+            #inline
+            pub fn #clear_name(&mut self) -> &mut Self {
+                self.#field_name = #empty;
+                #reset_flag
+                self
+            }
         }
-        quote! { #field_name : ::core::option::Option::None }
     });
-    let generics = &ast.generics;
+
+    let missing_fields = fields.iter().filter(|f| is_required(f) || is_flatten(f)).map(|f| {
+        let field_name = &f.ident;
+        let cfg = field_cfg(f);
+        let name = field_name_str(f);
+        if is_flatten(f) {
+            let prefix = format!("{name}.");
+            return quote! {
+                #cfg
+                for name in self.#field_name.missing_fields() {
+                    missing.push(alloc::borrow::Cow::Owned(alloc::format!("{}{}", #prefix, name)));
+                }
+            }
+        }
+        let is_set = is_set_expr(f);
+        let name = error_value(&name, owned_errors);
+        quote! {
+            #cfg
+            if !(#is_set) {
+                missing.push(#name);
+            }
+        }
+    }).collect::<Vec<_>>();
+
+    let missing_fields_body = if missing_fields.is_empty() {
+        quote! { alloc::vec::Vec::new() }
+    } else {
+        quote! {
+            let mut missing = alloc::vec::Vec::new();
+            #( #missing_fields )*
+            missing
+        }
+    };
+
+    let empty_fields = fields.iter().filter(|f| !is_disabled(f)).map(|f| {
+        let field_name = &f.ident;
+        let empty = if is_const && is_start(f) { quote!( ::core::option::Option::Some(#field_name) ) } else { empty_value(f) };
+        let cfg = field_cfg(f);
+        quote! { #cfg #field_name : #empty }
+    }).chain(duplicate_flags.iter().map(|(cfg, flag)| quote! { #cfg #flag : false }))
+      .chain(marker.iter().map(|_| quote! { #marker_name : ::core::marker::PhantomData }));
+    let generics = input.generics;
     let wher = &generics.where_clause;
     let stripped_generics = get_stripped_generics(generics, false);
     let generics_no_defaults = get_stripped_generics(generics, true);
 
-    let vis = &ast.vis;
+    let vis = input.vis;
 
     let build_fields = fields.iter().map(|f| {
         let field_name = &f.ident;
-        quote! { #field_name }
+        let cfg = field_cfg(f);
+        quote! { #cfg #field_name }
     });
 
 
-    let is_infallible = find_attr_nameval_bool(&ast.attrs, "infallible").is_ok_and(|v| v);
-    if is_infallible {
-        for field in fields.iter() {
-            if !is_optional(field) && !is_each(field) && find_attr_nameval_expr(&field.attrs, "def").is_err() {
-                return quote! {compile_error!("Infallible builder must have all it's fields \
-                    marked as either optional, or provide with a default value for them"); }.into()
-            }
+    let groups = field_groups(fields)?;
+    let mut build_checks = group_checks(&groups, owned_errors);
+    build_checks.extend(dependency_checks(fields, owned_errors)?);
+    build_checks.extend(duplicate_checks(fields, owned_errors)?);
+    let build_validations = value_checks(fields, owned_errors);
+
+    let can_fail = fields.iter().any(|f| is_required(f) || is_flatten(f))
+                   || !build_checks.is_empty()
+                   || !build_validations.is_empty();
+    let is_fallible = find_attr_nameval_bool(input.attrs, "fallible").is_ok_and(|v| v);
+    let is_infallible = match find_attr_nameval_bool(input.attrs, "infallible") {
+        Ok(infallible) => infallible,
+        Err(_) => !is_fallible && !can_fail,
+    };
+    if is_infallible && is_fallible {
+        return Err(syn::Error::new(Span::call_site(), "A builder can't be both fallible and infallible"));
+    }
+    if is_infallible && fields.iter().any(is_required) {
+        return Err(syn::Error::new(Span::call_site(), "Infallible builder must have all it's fields \
+            marked as either optional, or provide with a default value for them"));
+    }
+    if is_infallible && (!build_checks.is_empty() || !build_validations.is_empty()) {
+        return Err(syn::Error::new(Span::call_site(), "Infallible builder can't have field constraints"));
+    }
+
+    let has_default = |f: &Field| {
+        find_attr_nameval(&f.attrs, "def").is_ok() || find_attr_nameval(&f.attrs, "default_with").is_ok()
+    };
+    if let Some(f) = fields.iter().find(|f| is_flatten(f) && (is_optional(f) || is_each(f) || is_disabled(f) || is_start(f) || has_default(f))) {
+        return Err(syn::Error::new(f.span(), "Flattened fields can't be optional, start fields, \
+            have a default value, or be disabled"));
+    }
+    for f in fields.iter().filter(|f| is_flatten(f)) {
+        suffixed_type(&f.ty, "")?;
+    }
+    if let Some(f) = fields.iter().find(|f| find_attr_nameval(&f.attrs, "default_with").is_ok() && (is_optional(f) || is_each(f))) {
+        return Err(syn::Error::new(f.span(), "default_with can't be used on optional, vec or map fields"));
+    }
+    if let Some(f) = fields.iter().find(|f| is_start(f) && (is_optional(f) || is_each(f) || is_disabled(f) || has_default(f))) {
+        return Err(syn::Error::new(f.span(), "Start fields can't be optional, \
+            have a default value, or be disabled"));
+    }
+
+    if is_const {
+        if let Target::Fn { .. } = input.target {
+            return Err(syn::Error::new(Span::call_site(), "const builders can only be derived"));
+        }
+        let unsupported = |f: &&Field| {
+            find_attr_nameval(&f.attrs, "vec").is_ok() || find_attr_nameval(&f.attrs, "map").is_ok()
+            || is_flatten(f) || find_attr_nameval(&f.attrs, "default_with").is_ok()
+            || find_attr_nameval(&f.attrs, "range").is_ok() || find_attr_nameval(&f.attrs, "len").is_ok()
+            || find_attr_nameval(&f.attrs, "group").is_ok()
+        };
+        if let Some(f) = fields.iter().find(unsupported) {
+            return Err(syn::Error::new(f.span(), "const builders don't support vec, map, flatten, \
+                default_with, range, len or group attributes"));
+        }
+        if find_attr_nameval(input.attrs, "pre_build").is_ok() || find_attr_nameval(input.attrs, "post_build").is_ok() {
+            return Err(syn::Error::new(Span::call_site(), "const builders don't support build hooks"));
         }
     }
 
-    let build_fields_let = fields.iter().map(|f| {
+    let reserved: &[&str] = match input.target {
+        Target::Struct(_) => &["build", "builder", "build_or_panic", "build_expect",
+                               "build_boxed", "build_rc", "build_arc", "missing_fields"],
+        Target::Fn { .. } => &["call", "call_or_panic", "call_expect", "missing_fields"],
+    };
+    for f in fields.iter().filter(|f| !is_disabled(f) && !is_flatten(f)) {
+        let (setter, _, _, _) = setter_signature(f);
+        let setter = setter.unraw().to_string();
+        if reserved.contains(&setter.as_str()) {
+            return Err(syn::Error::new(f.span(), format!("The setter \"{setter}\" collides with a method generated by the builder")));
+        }
+    }
+
+    let sorted_fields = sort_by_dependencies(fields)?;
+
+    let default_with_name = |f: &Field| {
+        let fname = f.ident.as_ref().unwrap();
+        new_ident(&format!("__default_{}", fname.unraw()), fname.span())
+    };
+
+    let build_defaults_with = fields.iter().filter_map(|f| {
         let field_name = &f.ident;
-        let expr =
-            if is_disabled(f) {
-                let Ok(lit) = find_attr_nameval_expr(&f.attrs, "def") else {
-                    panic!("Disabled field must have a builder(def = ...) attribute");
+        let fun = find_attr_nameval_expr(&f.attrs, "default_with").ok()?;
+        let default_name = default_with_name(f);
+        let cfg = field_cfg(f);
+        Some(if is_disabled(f) {
+            quote! { #cfg let #default_name = #fun(&self); }
+        } else {
+            quote! {
+                #cfg
+                let #default_name = if self.#field_name.is_none() {
+                    ::core::option::Option::Some(#fun(&self))
+                } else {
+                    ::core::option::Option::None
                 };
-                quote! { #lit }
             }
-            else if is_optional(f) {
+        })
+    });
+
+    /* const builders can't drop any value, so the required fields are checked
+     * before taking the fields out of the builder */
+    let const_required_checks = sorted_fields.iter().filter(|f| is_const && is_required(f)).map(|f| {
+        let field_name = &f.ident;
+        let msg = format!("{} is not set", field_name_str(f));
+        let cfg = field_cfg(f);
+        quote! {
+            #cfg
+            if self.#field_name.is_none() {
+                return ::core::result::Result::Err(#msg);
+            }
+        }
+    }).collect::<Vec<_>>();
+
+    let build_fields_let = sorted_fields.into_iter().map(|f| {
+        let field_name = &f.ident;
+        let has_default_with = find_attr_nameval(&f.attrs, "default_with").is_ok();
+        let default_name = default_with_name(f);
+        let take = quote! { self.#field_name.take() };
+        let expr =
+            if is_const && !is_disabled(f) {
+                if is_optional(f) {
+                    take
+                } else if let Ok(lit) = find_attr_nameval_expr(&f.attrs, "def") {
+                    quote! {{
+                        let #field_name = #take;
+                        if #field_name.is_some() {
+                            #field_name.unwrap()
+                        } else {
+                            let _ = ::core::mem::ManuallyDrop::new(#field_name);
+                            #lit
+                        }
+                    }}
+                } else {
+                    quote! { #take.unwrap() }
+                }
+            }
+            else if is_disabled(f) {
+                if has_default_with {
+                    quote! { #default_name }
+                } else {
+                    let Ok(lit) = find_attr_nameval_expr(&f.attrs, "def") else {
+                        panic!("Disabled field must have a builder(def = ...) or builder(default_with = ...) attribute");
+                    };
+                    quote! { #lit }
+                }
+            }
+            else if is_optional(f) || is_start(f) {
                 quote! { self.#field_name }
+            } else if is_flatten(f) {
+                if is_infallible {
+                    quote! { ::core::convert::Into::into(self.#field_name) }
+                } else {
+                    let prefix = format!("{}: ", field_name_str(f));
+                    quote! {
+                        self.#field_name.__builders_try_build().map_err(|err| {
+                            alloc::borrow::Cow::Owned(alloc::format!("{}{}", #prefix, err))
+                        })?
+                    }
+                }
             } else if let Ok(lit) = find_attr_nameval_expr(&f.attrs, "def"){
                 quote! { self.#field_name.unwrap_or_else(|| #lit .into()) }
-            }  else if is_each(f) {
+            } else if is_each(f) {
                 quote! { self. #field_name }
+            } else if has_default_with {
+                quote! { self.#field_name.unwrap_or_else(|| #default_name.unwrap()) }
             } else {
-                quote! { self.#field_name.ok_or(stringify!("{} is not set", #field_name))? }
+                let msg = error_value(&format!("{} is not set", field_name_str(f)), owned_errors);
+                quote! { self.#field_name.ok_or(#msg)? }
             };
-        quote! { let #field_name = #expr ; }
+        let cfg = field_cfg(f);
+        quote! { #cfg let #field_name = #expr ; }
     });
 
-    let generics_params = &ast.generics.params;
+    let generics_params = &generics.params;
 
-    let clone_where_token = if wher.is_none() { quote! { where }} else { quote! {} };
+    /* The where clause of the generics, with `bound` added to every type parameter */
+    let where_with_bound = |bound: TokenStream| {
+        let mut generics = generics.clone();
+        let params = generics.type_params().map(|tp| tp.ident.clone()).collect::<Vec<_>>();
+        let where_clause = generics.make_where_clause();
+        for id in params {
+            where_clause.predicates.push(parse_quote!( #id : #bound ));
+        }
+        generics.where_clause
+    };
 
-    let must_clone = find_attr_nameval_bool(&ast.attrs, "clone").is_ok_and(|v| v);
+    let must_clone = find_attr_nameval_bool(input.attrs, "clone").is_ok_and(|v| v);
 
     let mut clone_impl = quote!{};
 
     if must_clone {
-        let where_clone = ast.generics.type_params().map(|tp| {
-            let id = &tp.ident;
-            quote! { #id : Clone }
-        });
+        let where_clone = where_with_bound(quote!( ::core::clone::Clone ));
         let fields_clone = fields.iter().filter(|f| !is_disabled(f)).map(|field| {
             let name = &field.ident;
-            quote! { #name : self . #name .clone() }
-        });
+            let cfg = field_cfg(field);
+            quote! { #cfg #name : self . #name .clone() }
+        }).chain(duplicate_flags.iter().map(|(cfg, flag)| quote! { #cfg #flag : self . #flag }))
+          .chain(marker.iter().map(|_| quote! { #marker_name : ::core::marker::PhantomData }));
 
         clone_impl = quote! {
-            impl<#generics_params> Clone for #builder_name #stripped_generics
-                #wher
-                #clone_where_token
-                #( #where_clone, )*
-            {
+            impl<#generics_params> ::core::clone::Clone for #builder_name #stripped_generics #where_clone {
                 fn clone(&self) -> Self {
                     Self {
                         #(#fields_clone ,)*
@@ -259,44 +1200,501 @@ pub (crate) fn builder_derive_impl(input: proc_macro::TokenStream) -> proc_macro
         };
     }
 
+    let debug_field = |f: &Field| {
+        let name = &f.ident;
+        let name_str = field_name_str(f);
+        let cfg = field_cfg(f);
+        if is_sensitive(f) {
+            quote! { #cfg debug.field(#name_str, &::core::format_args!("<redacted>")); }
+        } else {
+            quote! { #cfg debug.field(#name_str, &self.#name); }
+        }
+    };
+    let where_debug = where_with_bound(quote!( ::core::fmt::Debug ));
+
+    let mut debug_impl = quote! {};
+    if find_attr_nameval_bool(input.attrs, "debug").is_ok_and(|v| v) {
+        let fields_debug = fields.iter().filter(|f| !is_disabled(f)).map(debug_field);
+        debug_impl = quote! {
+            impl #generics_no_defaults ::core::fmt::Debug for #builder_name #stripped_generics #where_debug {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    let mut debug = f.debug_struct(stringify!(#builder_name));
+                    #( #fields_debug )*
+                    debug.finish()
+                }
+            }
+        };
+    }
+    if find_attr_nameval_bool(input.attrs, "debug_target").is_ok_and(|v| v) {
+        let Target::Struct(ident) = input.target else {
+            return Err(syn::Error::new(Span::call_site(), "debug_target can only be used when deriving Builder"));
+        };
+        let fields_debug = fields.iter().map(debug_field);
+        debug_impl.extend(quote! {
+            impl #generics_no_defaults ::core::fmt::Debug for #ident #stripped_generics #where_debug {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    let mut debug = f.debug_struct(stringify!(#ident));
+                    #( #fields_debug )*
+                    debug.finish()
+                }
+            }
+        });
+    }
+
+    let derives = find_attr_lists(input.attrs, "derive");
+    let builder_attrs = find_attr_lists(input.attrs, "attr");
+    for derive in &derives {
+        let paths = syn::parse::Parser::parse2(Punctuated::<syn::Path, Comma>::parse_terminated, derive.clone())?;
+        for path in paths {
+            let Some(name) = path.segments.last().map(|s| s.ident.to_string()) else { continue };
+            let conflict = match name.as_str() {
+                "Clone" if must_clone => "derive(Clone) conflicts with #[builder(clone)]",
+                "Debug" if fields.iter().any(is_sensitive) => "derive(Debug) would print the sensitive fields, use #[builder(debug)] instead",
+                "Debug" if find_attr_nameval_bool(input.attrs, "debug").is_ok_and(|v| v) => "derive(Debug) conflicts with #[builder(debug)]",
+                "Default" if matches!(input.target, Target::Struct(_)) && !fields.iter().any(is_start) => "The builder already implements Default",
+                _ => continue,
+            };
+            return Err(syn::Error::new(path.span(), conflict));
+        }
+    }
+
+    let (output, build_name) = match &input.target {
+        Target::Struct(ident) => (quote! { #ident #stripped_generics }, Ident::new("build", Span::call_site())),
+        Target::Fn { output, .. } => (output.clone(), Ident::new("call", Span::call_site())),
+    };
+
     let build_fn_return = if is_infallible {
-        quote! { #ident #stripped_generics }
+        quote! { #output }
     } else {
-        quote! { ::core::result::Result<#ident #stripped_generics, &'static str>  }
+        quote! { ::core::result::Result<#output, #error_ty>  }
     };
 
-    let mut build_self_struct = quote! {
-        #ident {
-            #( #build_fields ,)*
+    /* Result is already must_use */
+    let build_attrs = if is_infallible { owned_attrs.clone() } else { inline.clone() };
+
+    let mut build_pointers = quote! {};
+
+    let mut build_or_panic = quote! {};
+
+    if !is_infallible {
+        let or_panic_name = Ident::new(&format!("{build_name}_or_panic"), Span::call_site());
+        let expect_name = Ident::new(&format!("{build_name}_expect"), Span::call_site());
+        let or_panic_doc = format!("Like [`Self::{build_name}`], but panics if it fails");
+        let expect_doc = format!("Like [`Self::{build_name}`], but panics with `msg` if it fails");
+        build_or_panic = quote! {
+            #[doc = #or_panic_doc]
+            #owned_attrs
+            #vis fn #or_panic_name(self) -> #output {
+                match self.#build_name() {
+                    ::core::result::Result::Ok(built) => built,
+                    ::core::result::Result::Err(err) => ::core::panic!("{}", err),
+                }
+            }
+
+            #[doc = #expect_doc]
+            #owned_attrs
+            #vis fn #expect_name(self, msg: &str) -> #output {
+                match self.#build_name() {
+                    ::core::result::Result::Ok(built) => built,
+                    ::core::result::Result::Err(err) => ::core::panic!("{}: {}", msg, err),
+                }
+            }
+        };
+    }
+
+    if let (true, Target::Struct(_)) = (alloc, &input.target) {
+        let wrap_built = |ptr: TokenStream| {
+            if is_infallible {
+                quote! { #ptr::new(self.build()) }
+            } else {
+                quote! { self.build().map(#ptr::new) }
+            }
+        };
+        let build_boxed = wrap_built(quote!(alloc::boxed::Box));
+        let build_rc = wrap_built(quote!(alloc::rc::Rc));
+        let build_arc = wrap_built(quote!(alloc::sync::Arc));
+
+        let build_ptr_return = |ptr: TokenStream| {
+            if is_infallible {
+                quote! { #ptr<#output> }
+            } else {
+                quote! { ::core::result::Result<#ptr<#output>, #error_ty> }
+            }
+        };
+        let build_boxed_return = build_ptr_return(quote!(alloc::boxed::Box));
+        let build_rc_return = build_ptr_return(quote!(alloc::rc::Rc));
+        let build_arc_return = build_ptr_return(quote!(alloc::sync::Arc));
+
+        build_pointers = quote! {
+            /// Builds the value into a `Box`
+            #build_attrs
+            #vis fn build_boxed(self) -> #build_boxed_return {
+                #build_boxed
+            }
+
+            /// Builds the value into an `Rc`
+            #build_attrs
+            #vis fn build_rc(self) -> #build_rc_return {
+                #build_rc
+            }
+
+            /// Builds the value into an `Arc`
+            #[cfg(target_has_atomic = "ptr")]
+            #build_attrs
+            #vis fn build_arc(self) -> #build_arc_return {
+                #build_arc
+            }
+        };
+    }
+
+    let mut conversion_impls = quote! {};
+
+    let mut flatten_items = quote! {};
+
+    let mut flatten_trait = quote! {};
+
+    for f in fields.iter().filter(|f| is_flatten(f)) {
+        let field_name = &f.ident;
+        let flatten_trait = suffixed_type(&f.ty, "Flatten")?;
+        let inner_builder = suffixed_type(&f.ty, "Builder")?;
+        let cfg = field_cfg(f);
+        flatten_items.extend(quote! {
+            #cfg
+            impl #generics_no_defaults #flatten_trait for #builder_name #stripped_generics #wher {
+                fn __builders_inner(&mut self) -> &mut #inner_builder {
+                    &mut self.#field_name
+                }
+            }
+        });
+    }
+
+    if find_attr_nameval_bool(input.attrs, "flattenable").is_ok_and(|v| v) {
+        let Target::Struct(ident) = &input.target else {
+            return Err(syn::Error::new(Span::call_site(), "flattenable can only be used when deriving Builder"));
+        };
+        /* The setters of a const builder can't be forwarded */
+        if is_const {
+            return Err(syn::Error::new(Span::call_site(), "const builders can't be flattenable"));
+        }
+        let trait_name = new_ident(&format!("{}Flatten", ident.unraw()), ident.span());
+        let has_invalid_each = |f: &Field| {
+            !is_each(f) && (find_attr_nameval(&f.attrs, "vec").is_ok() || find_attr_nameval(&f.attrs, "map").is_ok())
+        };
+        let setters = fields.iter().filter(|f| !is_disabled(f) && !is_flatten(f) && !has_invalid_each(f)).map(|f| {
+            let (name, set_name, params, args) = setter_signature(f);
+            let cfg = field_cfg(f);
+            let docs = get_field_docs(f, &format!("Sets `{}`", field_name_str(f)));
+            quote! {
+                #cfg
+                #docs
+                #owned_attrs
+                fn #name(mut self, #params) -> Self {
+                    self.__builders_inner().#set_name(#args);
+                    self
+                }
+
+                #cfg
+                #docs
+                #inline
+                fn #set_name(&mut self, #params) -> &mut Self {
+                    self.__builders_inner().#set_name(#args);
+                    self
+                }
+            }
+        });
+        let try_build = if is_infallible {
+            quote! { ::core::result::Result::Ok(self.build()) }
+        } else {
+            quote! { self.build() }
+        };
+        flatten_trait = quote! {
+            #[doc = concat!("Exposes the setters of [`", stringify!(#builder_name), "`] on builders \
+                             that flatten a field of type [`", stringify!(#ident), "`]")]
+            ///
+            /// If the outer builder has a setter with the same name as one of
+            /// this trait, the setter of the outer builder takes precedence.
+            #[allow(dead_code)]
+            #vis trait #trait_name #generics_no_defaults : Sized #wher {
+                #[doc(hidden)]
+                fn __builders_inner(&mut self) -> &mut #builder_name #stripped_generics;
+
+                #( #setters )*
+            }
+        };
+        flatten_items.extend(quote! {
+            impl #generics_no_defaults #builder_name #stripped_generics #wher {
+                #[doc(hidden)]
+                #[allow(dead_code)]
+                pub fn __builders_try_build(self) -> ::core::result::Result<#output, #error_ty> {
+                    #try_build
+                }
+            }
+        });
+    }
+
+    if let Target::Struct(ident) = input.target {
+        if !fields.iter().any(is_start) {
+            conversion_impls.extend(quote! {
+                impl #generics_no_defaults ::core::default::Default for #builder_name #stripped_generics #wher {
+                    fn default() -> Self {
+                        #ident::builder()
+                    }
+                }
+            });
+        }
+
+        conversion_impls.extend(if is_infallible {
+            quote! {
+                impl #generics_no_defaults ::core::convert::From<#builder_name #stripped_generics> for #output #wher {
+                    fn from(builder: #builder_name #stripped_generics) -> Self {
+                        builder.build()
+                    }
+                }
+            }
+        } else {
+            quote! {
+                impl #generics_no_defaults ::core::convert::TryFrom<#builder_name #stripped_generics> for #output #wher {
+                    type Error = #error_ty;
+
+                    fn try_from(builder: #builder_name #stripped_generics) -> ::core::result::Result<Self, Self::Error> {
+                        builder.build()
+                    }
+                }
+            }
+        });
+    }
+
+    if find_attr_nameval_bool(input.attrs, "runtime").is_ok_and(|v| v) {
+        if let Target::Struct(ident) = input.target {
+            let error = if is_infallible {
+                quote! { ::core::convert::Infallible }
+            } else {
+                error_ty.clone()
+            };
+            let build = if is_infallible {
+                quote! { ::core::result::Result::Ok(self.build()) }
+            } else {
+                quote! { self.build() }
+            };
+            conversion_impls.extend(quote! {
+                impl #generics_no_defaults ::builders_runtime::Builder for #builder_name #stripped_generics #wher {
+                    type Output = #output;
+                    type Error = #error;
+
+                    fn build(self) -> ::core::result::Result<Self::Output, Self::Error> {
+                        #build
+                    }
+                }
+            });
+            if !fields.iter().any(is_start) {
+                conversion_impls.extend(quote! {
+                    impl #generics_no_defaults ::builders_runtime::Buildable for #output #wher {
+                        type Builder = #builder_name #stripped_generics;
+
+                        fn builder() -> Self::Builder {
+                            #ident::builder()
+                        }
+                    }
+                });
+            }
         }
+    }
+
+    let mut build_self_struct = match &input.target {
+        Target::Struct(ident) => quote! {
+            #ident {
+                #( #build_fields ,)*
+            }
+        },
+        Target::Fn { path, .. } => quote! {
+            #path( #( #build_fields ),* )
+        },
     };
 
+    if let Ok(post_build) = find_attr_nameval_expr(input.attrs, "post_build") {
+        build_self_struct = quote! {{
+            let mut built = #build_self_struct;
+            #post_build(&mut built);
+            built
+        }};
+    }
+
     if !is_infallible {
         build_self_struct = quote! { Ok( #build_self_struct ) };
     }
 
-    quote! {
+    let (build_self, pre_build) = match find_attr_nameval_expr(input.attrs, "pre_build") {
+        Ok(pre_build) => (quote! { mut self }, quote! { #pre_build(&mut self); }),
+        Err(_) => (quote! { self }, quote! {}),
+    };
+
+    let mut const_check = quote! {};
+    let (build_self, build_body) = if is_const {
+        /* The values are moved out of the builder, which is then forgotten
+         * instead of dropped, since destructors can't run in const fns */
+        let check = if is_infallible {
+            quote! {}
+        } else {
+            const_check = quote! {
+                #[doc(hidden)]
+                #inline
+                const fn __builders_check(&self) -> ::core::result::Result<(), &'static str> {
+                    #( #build_checks )*
+                    #( #const_required_checks )*
+                    ::core::result::Result::Ok(())
+                }
+            };
+            quote! {
+                if let ::core::result::Result::Err(err) = self.__builders_check() {
+                    let _ = ::core::mem::ManuallyDrop::new(self);
+                    return ::core::result::Result::Err(err);
+                }
+            }
+        };
+        let build_self = if fields.iter().all(is_disabled) { quote! { self } } else { quote! { mut self } };
+        (build_self, quote! {
+            #check
+            #( #build_fields_let )*
+            let _ = ::core::mem::ManuallyDrop::new(self);
+            #build_self_struct
+        })
+    } else {
+        (build_self, quote! {
+            #pre_build
+            #( #build_checks )*
+            #( #build_defaults_with )*
+            #( #build_fields_let )*
+            #( #build_validations )*
+            #build_self_struct
+        })
+    };
+
+    let entry_generics = input.entry_generics.as_ref().map(|g| {
+        let params = get_stripped_generics(g, true);
+        let wher = &g.where_clause;
+        (params, wher)
+    });
+    let (entry_params, entry_where) = match entry_generics {
+        Some((params, wher)) => (params, quote!(#wher)),
+        None => (quote!(), quote!()),
+    };
+
+    let (mut builder_doc, mut build_doc) = match &input.target {
+        Target::Struct(ident) => (format!("Builder for [`{ident}`]"), vec![format!("Builds a new [`{ident}`]")]),
+        Target::Fn { path, .. } => {
+            let path = path.to_string().replace(' ', "");
+            (format!("Builder for the arguments of `{path}`"), vec![format!("Calls `{path}` with the arguments of the builder")])
+        }
+    };
+    if is_const {
+        builder_doc.push_str("\n\nDestructors can't run in const fns, so the values replaced by a setter, \
+                              and the ones left in the builder when the build fails, are leaked instead of dropped.");
+    }
+    let mut build_errors = Vec::new();
+    let required = fields.iter().filter(|f| is_required(f))
+                         .map(|f| format!("`{}`", field_name_str(f)))
+                         .collect::<Vec<_>>();
+    if !required.is_empty() {
+        build_errors.push(format!("* Any of the required fields is not set: {}", required.join(", ")));
+    }
+    if !build_checks.is_empty() || !build_validations.is_empty() {
+        build_errors.push("* Any of the constraints of the fields is not met".to_owned());
+    }
+    if fields.iter().any(is_flatten) {
+        build_errors.push("* Any of the flattened builders fails".to_owned());
+    }
+    if !is_infallible && !build_errors.is_empty() {
+        build_doc.push(String::new());
+        build_doc.push("# Errors".to_owned());
+        build_doc.push("Fails if:".to_owned());
+        build_doc.extend(build_errors);
+    }
+
+    let methods_cfg = fields.iter().filter(|f| !is_disabled(f) && !is_flatten(f)).map(field_cfg);
+
+    let mut missing_fields_fn = quote! {};
+    if alloc {
+        missing_fields_fn = quote! {
+            /// Returns the names of the required fields that haven't been set yet
+            #inline
+            #vis fn missing_fields(&self) -> alloc::vec::Vec<#error_ty> {
+                #missing_fields_body
+            }
+        };
+    }
+
+    let extern_alloc = if alloc { quote!( extern crate alloc; ) } else { quote!() };
+
+    let items = quote! {
+        #( #[derive(#derives)] )*
+        #( #[#builder_attrs] )*
+        #[doc = #builder_doc]
         #vis struct #builder_name #generics {
             #( #builder_fields ,)*
         }
 
         #clone_impl
 
-        impl #generics_no_defaults #builder_name #stripped_generics #wher {
-            #vis fn build(self) -> #build_fn_return {
-                #( #build_fields_let )*
-                #build_self_struct
+        #debug_impl
+
+        #flatten_trait
+
+        /* Scoped, so that it doesn't collide with the alloc crate of other builders */
+        const _: () = {
+            #extern_alloc
+
+            #conversion_impls
+
+            #flatten_items
+
+            impl #generics_no_defaults #builder_name #stripped_generics #wher {
+                #( #[doc = #build_doc] )*
+                #build_attrs
+                #vis #constness fn #build_name(#build_self) -> #build_fn_return {
+                    #build_body
+                }
+
+                #const_check
+
+                #build_or_panic
+
+                #build_pointers
+
+                #missing_fields_fn
+            }
+        };
+
+        #(
+            #methods_cfg
+            impl #generics_no_defaults #builder_name #stripped_generics #wher {
+                #builder_methods
+
+                #introspection_methods
             }
+        )*
+    };
 
-            #( #builder_methods )*
+    let start_args = fields.iter().filter(|f| is_start(f)).map(|f| {
+        let field_name = &f.ident;
+        let ty = &f.ty;
+        let cfg = field_cfg(f);
+        if is_const {
+            quote! { #cfg #field_name: #ty }
+        } else {
+            quote! { #cfg #field_name: impl ::core::convert::Into<#ty> }
         }
+    });
 
-        impl #generics_no_defaults #ident #stripped_generics #wher {
-            #vis fn builder() -> #builder_name #stripped_generics {
-                #builder_name {
-                    #( #empty_fields ,)*
-                }
+    let entry_doc = format!("Creates a new [`{builder_name}`]");
+    let entry = quote! {
+        #[doc = #entry_doc]
+        #inline
+        #vis #constness fn #entry_name #entry_params ( #( #start_args ),* ) -> #builder_name #stripped_generics #entry_where {
+            #builder_name {
+                #( #empty_fields ,)*
             }
         }
-    }.into()
+    };
+
+    Ok((items, entry))
 }
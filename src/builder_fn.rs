@@ -0,0 +1,254 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, punctuated::Punctuated, spanned::Spanned, token::Comma,
+    visit_mut::{self, VisitMut}, Attribute, Field, FieldMutability, FnArg, GenericParam, Generics,
+    Ident, ImplItem, Item, ItemFn, ItemImpl, Lifetime, ParenthesizedGenericArguments, Pat,
+    PatIdent, ReturnType, Signature, Type, TypeBareFn, TypePath, TypeReference, Visibility,
+};
+
+use crate::builder::{gen_builder, BuilderInput, Target};
+use crate::util::new_ident;
+
+/// Gives a name to the elided lifetimes of the parameters, since
+/// the builder needs to store them.
+/// It also replaces `Self` with the type of the impl block.
+struct TypeFixer<'a> {
+    self_ty: Option<&'a Type>,
+    /// Lifetimes generated for the elided ones
+    lifetimes: Vec<Lifetime>,
+    /// When fixing the return type, the elided lifetimes are replaced
+    /// with the only lifetime of the parameters, if there's one.
+    output: bool,
+}
+
+impl TypeFixer<'_> {
+    fn elided(&mut self) -> Option<Lifetime> {
+        if self.output {
+            match &self.lifetimes[..] {
+                [lt] => Some(lt.clone()),
+                _ => None,
+            }
+        } else {
+            let lt = Lifetime::new(&format!("'__b{}", self.lifetimes.len()), Span::call_site());
+            self.lifetimes.push(lt.clone());
+            Some(lt)
+        }
+    }
+}
+
+impl VisitMut for TypeFixer<'_> {
+    fn visit_type_reference_mut(&mut self, r: &mut TypeReference) {
+        if r.lifetime.is_none() {
+            r.lifetime = self.elided();
+        }
+        visit_mut::visit_type_reference_mut(self, r);
+    }
+
+    fn visit_lifetime_mut(&mut self, lt: &mut Lifetime) {
+        if lt.ident == "_" {
+            if let Some(new) = self.elided() {
+                *lt = new;
+            }
+        }
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let (Type::Path(TypePath { qself: None, path }), Some(self_ty)) = (&*ty, self.self_ty) {
+            if path.is_ident("Self") {
+                *ty = self_ty.clone();
+                return;
+            }
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+
+    /* The lifetimes inside fn(&T) and Fn(&T) are higher ranked */
+    fn visit_type_bare_fn_mut(&mut self, _: &mut TypeBareFn) {}
+    fn visit_parenthesized_generic_arguments_mut(&mut self, _: &mut ParenthesizedGenericArguments) {}
+}
+
+fn is_builder_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("builder")
+}
+
+fn camel_case(ident: &Ident) -> String {
+    ident.unraw()
+         .to_string()
+         .split('_')
+         .filter(|s| !s.is_empty())
+         .map(|s| {
+             let mut chars = s.chars();
+             chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+         })
+         .collect()
+}
+
+/// Appends the params of `other` into `generics`, keeping the
+/// lifetimes before the rest of the parameters.
+fn merge_generics(generics: &mut Generics, other: &Generics) {
+    let (lifetimes, rest): (Vec<_>, Vec<_>) =
+        generics.params.iter().cloned()
+        .chain(other.params.iter().cloned())
+        .partition(|p| matches!(p, GenericParam::Lifetime(_)));
+    generics.params = lifetimes.into_iter().chain(rest).collect();
+    if let Some(wher) = &other.where_clause {
+        generics.make_where_clause().predicates.extend(wher.predicates.iter().cloned());
+    }
+}
+
+fn lifetime_generics(lifetimes: &[Lifetime]) -> Generics {
+    let params = lifetimes.iter().map(|lt| -> GenericParam { parse_quote!(#lt) });
+    parse_quote!(< #( #params ),* >)
+}
+
+/// Builds the builder for the given function signature.
+///
+/// Returns the builder items, and the function that creates the builder.
+fn fn_builder(attrs: &[Attribute], vis: &Visibility, sig: &mut Signature,
+              builder_name: Ident, impl_block: Option<&ItemImpl>) -> syn::Result<(TokenStream, TokenStream)> {
+    if sig.asyncness.is_some() || sig.unsafety.is_some() || sig.variadic.is_some() {
+        return Err(syn::Error::new(sig.span(), "async, unsafe and variadic functions are not supported by #[builder]"));
+    }
+
+    let mut fixer = TypeFixer {
+        self_ty: impl_block.map(|i| &*i.self_ty),
+        lifetimes: Vec::new(),
+        output: false,
+    };
+    let mut fields: Punctuated<Field, Comma> = Punctuated::new();
+
+    for arg in sig.inputs.iter_mut() {
+        let arg = match arg {
+            FnArg::Receiver(r) => return Err(syn::Error::new(r.span(), "Methods with a receiver are not supported by #[builder]")),
+            FnArg::Typed(arg) => arg,
+        };
+        let Pat::Ident(PatIdent { ident, .. }) = &*arg.pat else {
+            return Err(syn::Error::new(arg.pat.span(), "Only identifier patterns are supported by #[builder]"));
+        };
+        if let Type::ImplTrait(_) = &*arg.ty {
+            return Err(syn::Error::new(arg.ty.span(), "impl Trait arguments are not supported by #[builder]"));
+        }
+        let mut ty = (*arg.ty).clone();
+        fixer.visit_type_mut(&mut ty);
+        fields.push(Field {
+            attrs: arg.attrs.clone(),
+            vis: Visibility::Inherited,
+            mutability: FieldMutability::None,
+            ident: Some(ident.clone()),
+            colon_token: Some(Default::default()),
+            ty,
+        });
+        arg.attrs.retain(|a| !is_builder_attr(a));
+    }
+
+    let mut output = match &sig.output {
+        ReturnType::Default => parse_quote!(()),
+        ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+    fixer.output = true;
+    fixer.visit_type_mut(&mut output);
+
+    let fresh_lifetimes = lifetime_generics(&fixer.lifetimes);
+
+    /* Generics of the function that creates the builder */
+    let mut entry_generics = sig.generics.clone();
+    merge_generics(&mut entry_generics, &fresh_lifetimes);
+
+    /* Generics of the builder struct */
+    let mut generics = impl_block.map(|i| i.generics.clone()).unwrap_or_default();
+    merge_generics(&mut generics, &entry_generics);
+
+    let fn_name = &sig.ident;
+    let path = match impl_block {
+        Some(i) => {
+            let self_ty = &i.self_ty;
+            quote!( <#self_ty>::#fn_name )
+        },
+        None => quote!( #fn_name ),
+    };
+
+    let input = BuilderInput {
+        vis,
+        attrs,
+        generics: &generics,
+        fields: &fields,
+        builder_name,
+        entry_name: new_ident(&format!("{}_builder", fn_name.unraw()), fn_name.span()),
+        entry_generics: Some(entry_generics),
+        target: Target::Fn { path, output: quote!(#output) },
+    };
+    gen_builder(&input)
+}
+
+fn attr_args(attr: TokenStream) -> Vec<Attribute> {
+    if attr.is_empty() {
+        Vec::new()
+    } else {
+        vec![parse_quote!(#[builder(#attr)])]
+    }
+}
+
+fn item_fn_builder(attrs: &[Attribute], mut item: ItemFn) -> syn::Result<TokenStream> {
+    let builder_name = new_ident(&format!("{}Builder", camel_case(&item.sig.ident)), item.sig.ident.span());
+    let (items, entry) = fn_builder(attrs, &item.vis, &mut item.sig, builder_name, None)?;
+    Ok(quote! {
+        #item
+
+        #items
+
+        #entry
+    })
+}
+
+fn item_impl_builder(mut item: ItemImpl) -> syn::Result<TokenStream> {
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(syn::Error::new(path.span(), "#[builder] can't be applied to trait impls"));
+    }
+    let Type::Path(TypePath { path, .. }) = &*item.self_ty else {
+        return Err(syn::Error::new(item.self_ty.span(), "#[builder] impl blocks must be for a named type"));
+    };
+    let self_name = path.segments.last().map(|s| s.ident.unraw().to_string()).unwrap_or_default();
+
+    let mut builders = Vec::new();
+    let mut entries = Vec::new();
+    let block = item.clone();
+
+    for impl_item in item.items.iter_mut() {
+        let ImplItem::Fn(f) = impl_item else { continue };
+        let Some(pos) = f.attrs.iter().position(is_builder_attr) else { continue };
+        let attr = f.attrs.remove(pos);
+        let attrs = match attr.meta {
+            syn::Meta::Path(_) => Vec::new(),
+            meta => attr_args(meta.require_list()?.tokens.clone()),
+        };
+        let builder_name = new_ident(&format!("{self_name}{}Builder", camel_case(&f.sig.ident)), f.sig.ident.span());
+        let (items, entry) = fn_builder(&attrs, &f.vis, &mut f.sig, builder_name, Some(&block))?;
+        builders.push(items);
+        entries.push(entry);
+    }
+
+    let ItemImpl { attrs, generics, self_ty, items, .. } = &item;
+    let wher = &generics.where_clause;
+
+    Ok(quote! {
+        #( #attrs )*
+        impl #generics #self_ty #wher {
+            #( #items )*
+
+            #( #entries )*
+        }
+
+        #( #builders )*
+    })
+}
+
+pub (crate) fn builder_attr_impl(attr: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let item = parse_macro_input!(input as Item);
+    let result = match item {
+        Item::Fn(f) => item_fn_builder(&attr_args(attr.into()), f),
+        Item::Impl(i) => item_impl_builder(i),
+        item => Err(syn::Error::new(item.span(), "#[builder] can only be applied to functions and impl blocks")),
+    };
+    result.unwrap_or_else(syn::Error::into_compile_error).into()
+}
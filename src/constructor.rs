@@ -1,18 +1,26 @@
 use quote::quote;
-use syn::{parse_macro_input, Attribute, DeriveInput, Expr, ExprLit, Field, Lit, LitBool, Meta, MetaNameValue};
+use syn::{ext::IdentExt, parse_macro_input, Attribute, DeriveInput, Expr, ExprLit, Field, Lit, LitBool, Meta, MetaNameValue};
 
 use crate::util::{self, get_stripped_generics};
 
 fn must_generate(f: &Field) -> bool {
     let attr = util::get_field_attr(&f.attrs, "constructor");
-    if let Some(Attribute{ meta: Meta::NameValue(MetaNameValue{ ref value, .. }), .. }) = attr.last() {
-        if let Expr::Lit(ExprLit{lit: Lit::Bool(LitBool{value, ..}), ..}) = value {
-            return *value;
-        }
+    if let Some(Attribute{ meta: Meta::NameValue(MetaNameValue{ value: Expr::Lit(ExprLit{lit: Lit::Bool(LitBool{value, ..}), ..}), .. }), .. }) = attr.last() {
+        return *value;
     }
     true
 }
 
+/// Returns the first line of the doc comments
+fn get_doc_summary(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| {
+        let Meta::NameValue(MetaNameValue { path, value: Expr::Lit(ExprLit { lit: Lit::Str(doc), .. }), .. }) = &attr.meta else { return None };
+        if !path.is_ident("doc") { return None }
+        let line = doc.value().trim().to_owned();
+        (!line.is_empty()).then_some(line)
+    })
+}
+
 pub (crate) fn constructor_derive_impl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let vis = &ast.vis;
@@ -24,29 +32,44 @@ pub (crate) fn constructor_derive_impl(input: proc_macro::TokenStream) -> proc_m
     };
 
     let args = fields.iter().filter_map(|f| {
-        if !must_generate(&f) { return None; }
+        if !must_generate(f) { return None; }
         let ident = util::get_field_ident(f);
         let ty = &f.ty;
+        let cfg = util::get_cfg_attrs(&f.attrs);
         Some(
         quote! {
-            #ident : impl std::convert::Into<#ty>
+            #( #cfg )*
+            #ident : impl ::core::convert::Into<#ty>
         })
     });
     let names = fields.iter().map(|f| {
         let ident = util::get_field_ident(f);
-        if must_generate(&f) {
-            quote!( #ident : #ident.into() )
+        let cfg = util::get_cfg_attrs(&f.attrs);
+        if must_generate(f) {
+            quote!( #( #cfg )* #ident : #ident.into() )
         } else {
-            quote!( #ident : std::default::Default::default() )
+            quote!( #( #cfg )* #ident : ::core::default::Default::default() )
         }
     });
 
+    let mut docs = vec![format!("Creates a new [`{name}`]")];
+    let arg_docs = fields.iter().filter(|f| must_generate(f)).filter_map(|f| {
+        let summary = get_doc_summary(&f.attrs)?;
+        Some(format!("* `{}`: {summary}", util::get_field_ident(f).unraw()))
+    }).collect::<Vec<_>>();
+    if !arg_docs.is_empty() {
+        docs.push(String::new());
+        docs.push("# Arguments".to_owned());
+        docs.extend(arg_docs);
+    }
+
     let generics = &ast.generics;
     let wher = &generics.where_clause;
     let stripped_generics = get_stripped_generics(generics, false);
 
     quote! {
         impl #generics #name #stripped_generics #wher {
+            #( #[doc = #docs] )*
             #vis fn new(
                 #( #args ,)*
             ) -> #name #stripped_generics {
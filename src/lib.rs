@@ -1,6 +1,8 @@
 mod util;
 #[cfg(feature = "builder")]
 mod builder;
+#[cfg(feature = "builder")]
+mod builder_fn;
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenTree;
@@ -12,6 +14,12 @@ pub fn builder_derive(input: TokenStream) -> TokenStream {
     builder::builder_derive_impl(input)
 }
 
+#[cfg(feature = "builder")]
+#[proc_macro_attribute]
+pub fn builder(attr: TokenStream, item: TokenStream) -> TokenStream {
+    builder_fn::builder_attr_impl(attr, item)
+}
+
 
 #[cfg(feature = "getters")]
 #[proc_macro_derive(Getters, attributes(getter,getters))]
@@ -75,13 +83,32 @@ pub fn auto_box_derive(input: TokenStream) -> TokenStream {
     let wher = &generics.where_clause;
 
     quote::quote! {
-        extern crate alloc;
+        const _: () = {
+            extern crate alloc;
+
+            impl #generics #ident #stripped #wher {
+                /// Moves the value into a `Box`
+                #vis fn as_box(self) -> alloc::boxed::Box<#ident #stripped> {
+                    alloc::boxed::Box::new(self)
+                }
 
-        impl #generics #ident #stripped #wher {
-            #vis fn as_box(self) -> ::alloc::boxed::Box<#ident #stripped> {
-                std::boxed::Box::new(self)
+                /// Moves the value into a pinned `Box`
+                #vis fn as_pin_box(self) -> ::core::pin::Pin<alloc::boxed::Box<#ident #stripped>> {
+                    alloc::boxed::Box::pin(self)
+                }
+
+                /// Moves the value into an `Rc`
+                #vis fn as_rc(self) -> alloc::rc::Rc<#ident #stripped> {
+                    alloc::rc::Rc::new(self)
+                }
+
+                /// Moves the value into an `Arc`
+                #[cfg(target_has_atomic = "ptr")]
+                #vis fn as_arc(self) -> alloc::sync::Arc<#ident #stripped> {
+                    alloc::sync::Arc::new(self)
+                }
             }
-        }
+        };
     }.into()
 }
 
@@ -133,7 +160,7 @@ pub fn wrap_enum_derive(input: TokenStream) -> TokenStream {
     let enum_name = enum_name.unwrap();
 
     quote::quote! {
-        impl #generics From<#ident #stripped> for #enum_name #wher {
+        impl #generics ::core::convert::From<#ident #stripped> for #enum_name #wher {
             fn from(value: #ident #stripped) -> Self {
                 #enum_name :: #field_name (value)
             }
@@ -1,7 +1,7 @@
-use syn::{punctuated::Punctuated, spanned::Spanned, token::Comma, AngleBracketedGenericArguments, Attribute, Data, DataStruct, DeriveInput, Field, Fields, FieldsNamed, GenericArgument, GenericParam, Generics, Ident, Meta, MetaList, MetaNameValue, Path, PathArguments, Type, TypePath};
+use syn::{punctuated::Punctuated, spanned::Spanned, token::Comma, AngleBracketedGenericArguments, Attribute, Data, DataStruct, DeriveInput, Field, Fields, FieldsNamed, GenericArgument, GenericParam, Generics, Meta, MetaList, MetaNameValue, Path, PathArguments, Type, TypePath};
 use quote::quote;
 
-pub (crate) fn get_inner_ty<'a>(ty: &'a syn::Type) -> Option<Vec<&'a syn::Type>> {
+pub (crate) fn get_inner_ty(ty: &syn::Type) -> Option<Vec<&syn::Type>> {
     let Type::Path(
             TypePath {
                 path : Path { ref segments, .. }, ..
@@ -67,21 +67,57 @@ pub (crate) fn get_named_struct(input: &DeriveInput) -> syn::Result<&Punctuated<
 }
 
 pub (crate) fn get_field_attr<'a>(attrs: &'a [Attribute], name: &str) -> Vec<&'a Attribute> {
-    attrs.iter().filter_map(|attr| {
+    attrs.iter().filter(|attr| {
         let segments = match &attr.meta {
             Meta::Path(Path { segments, .. }) => segments,
             Meta::List(MetaList { path: Path { segments,.. },.. } ) => segments,
             Meta::NameValue(MetaNameValue { path: Path { segments,.. },.. }) => segments,
         };
-        if let Some(segment) = segments.last() {
-            if segment.ident == Ident::new(name, segment.ident.span()) {
-                return Some(attr);
-            }
-        }
-        None
+        segments.last().is_some_and(|segment| segment.ident == name)
+    }).collect()
+}
+
+/// Returns the `#[cfg]` attributes of a field, so they can be copied
+/// onto every item generated for it.
+///
+/// `#[cfg_attr]` attributes are only returned when all the attributes
+/// they expand to are `cfg`s, since any other attribute is specific to the field.
+#[cfg(any(feature = "builder", feature = "constructor", feature = "getters", feature = "setters"))]
+pub (crate) fn get_cfg_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs.iter().filter(|attr| {
+        if attr.path().is_ident("cfg") { return true }
+        if !attr.path().is_ident("cfg_attr") { return false }
+        let Ok(list) = attr.meta.require_list() else { return false };
+        let Ok(metas) = list.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated) else { return false };
+        metas.len() > 1 && metas.iter().skip(1).all(|m| m.path().is_ident("cfg"))
     }).collect()
 }
 
+/// Returns the doc comments of the field if it has any, or
+/// `default` otherwise.
+#[cfg(any(feature = "builder", feature = "getters", feature = "setters"))]
+pub (crate) fn get_field_docs(f: &Field, default: &str) -> proc_macro2::TokenStream {
+    let docs = f.attrs.iter().filter(|attr| attr.path().is_ident("doc")).collect::<Vec<_>>();
+    if docs.is_empty() {
+        quote!( #[doc = #default] )
+    } else {
+        quote!( #( #docs )* )
+    }
+}
+
+/// Creates an identifier for a generated name, like `set_{field}`.
+///
+/// The name must be built from unraw'd identifiers. If it's a keyword,
+/// the raw identifier is returned instead.
+#[cfg(any(feature = "builder", feature = "getters", feature = "setters"))]
+pub (crate) fn new_ident(name: &str, span: proc_macro2::Span) -> syn::Ident {
+    if syn::parse_str::<syn::Ident>(name).is_ok() {
+        syn::Ident::new(name, span)
+    } else {
+        syn::Ident::new_raw(name, span)
+    }
+}
+
 #[inline(always)]
 pub (crate) fn get_field_ident(field: &Field) -> &syn::Ident {
     let ident = field.ident.as_ref();
@@ -95,11 +131,11 @@ pub (crate) fn gen_for_each_field<F>(ast: &DeriveInput, attr: &str, prefix: &str
 where
     F: Fn(&Field,bool) -> proc_macro2::TokenStream
 {
-    use syn::{Expr, ExprLit, Lit, LitBool};
+    use syn::{ext::IdentExt, Expr, ExprLit, Lit, LitBool};
 
     let name = &ast.ident;
     let vis = &ast.vis;
-    let fields = match get_named_struct(&ast) {
+    let fields = match get_named_struct(ast) {
         Ok(fields) => fields,
         Err(err) => return err.into_compile_error().into()
     };
@@ -119,9 +155,17 @@ where
         }
         }
         let ident = get_field_ident(f);
-        let new_ident = Ident::new(&format!("{prefix}{}", ident), f.span());
+        let new_ident = new_ident(&format!("{prefix}{}", ident.unraw()), f.span());
         let code = fun(f,each);
+        let cfg = get_cfg_attrs(&f.attrs);
+        let default_doc = match attr {
+            "getter" => format!("Returns a reference to `{}`", ident.unraw()),
+            _ => format!("Sets `{}`", ident.unraw()),
+        };
+        let docs = get_field_docs(f, &default_doc);
         Some(quote! {
+            #( #cfg )*
+            #docs
             #vis fn #new_ident #code
         })
     });
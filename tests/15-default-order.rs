@@ -0,0 +1,35 @@
+// Default values may reference other fields of the struct, regardless
+// of the order in which they are declared. The builder evaluates them
+// following their dependencies.
+
+use builders::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(def = { format!("{} {}", executable, args.join(" ")) })]
+    line: String,
+    #[builder(def = { args.len() })]
+    argc: usize,
+    executable: String,
+    #[builder(vec = "arg")]
+    args: Vec<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg("--release")
+        .build()
+        .unwrap();
+    assert_eq!(command.line, "cargo build --release");
+    assert_eq!(command.argc, 2);
+
+    let command = Command::builder()
+        .executable("cargo")
+        .line("rustc")
+        .build()
+        .unwrap();
+    assert_eq!(command.line, "rustc");
+    assert_eq!(command.argc, 0);
+}
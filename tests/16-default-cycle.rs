@@ -0,0 +1,15 @@
+// Default values can't depend on each other in a circular way.
+
+use builders::Builder;
+
+#[derive(Builder)]
+pub struct Rect {
+    #[builder(def = { height * 2 })]
+    width: u32,
+    #[builder(def = { area / width })]
+    height: u32,
+    #[builder(def = { width * height })]
+    area: u32,
+}
+
+fn main() {}
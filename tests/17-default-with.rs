@@ -0,0 +1,50 @@
+// A default can be computed by a function that receives the builder. This
+// allows the default to depend on whether other fields were explicitly set.
+
+use builders::Builder;
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    #[builder(optional = true)]
+    tls_cert: Option<String>,
+    #[builder(default_with = default_port)]
+    port: u16,
+    #[builder(disabled = true, default_with = self::defaults::scheme)]
+    scheme: &'static str,
+}
+
+fn default_port(b: &ServerBuilder) -> u16 {
+    if b.tls_cert.is_some() { 443 } else { 80 }
+}
+
+mod defaults {
+    pub fn scheme(b: &super::ServerBuilder) -> &'static str {
+        if b.tls_cert.is_some() { "https" } else { "http" }
+    }
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("example.com")
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 80);
+    assert_eq!(server.scheme, "http");
+
+    let server = Server::builder()
+        .host("example.com")
+        .tls_cert("cert.pem")
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 443);
+    assert_eq!(server.scheme, "https");
+
+    let server = Server::builder()
+        .host("example.com")
+        .tls_cert("cert.pem")
+        .port(8443u16)
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 8443);
+}
@@ -0,0 +1,42 @@
+// The builder can be inspected to know which fields have already been set,
+// and which required ones are still missing, without calling build().
+
+use builders::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+pub struct Request {
+    url: String,
+    method: String,
+    #[builder(def = 1.1)]
+    version: f32,
+    #[builder(optional = true)]
+    body: Option<Vec<u8>>,
+    #[builder(map = "header")]
+    headers: HashMap<String, String>,
+}
+
+fn main() {
+    let builder = Request::builder();
+    assert_eq!(builder.missing_fields(), ["url", "method"]);
+    assert!(!builder.is_url_set());
+    assert!(!builder.is_headers_set());
+    assert_eq!(builder.get_url(), None);
+    assert_eq!(builder.get_headers(), None);
+
+    let builder = builder
+        .url("example.com")
+        .body(vec![1, 2, 3])
+        .header("Host", "example.com");
+    assert_eq!(builder.missing_fields(), ["method"]);
+    assert!(builder.is_url_set());
+    assert!(builder.is_body_set());
+    assert!(!builder.is_version_set());
+    assert_eq!(builder.get_url().map(String::as_str), Some("example.com"));
+    assert_eq!(builder.get_body(), Some(&vec![1, 2, 3]));
+    assert_eq!(builder.get_headers().map(HashMap::len), Some(1));
+
+    let builder = builder.method("GET");
+    assert!(builder.missing_fields().is_empty());
+    assert!(builder.build().is_ok());
+}
@@ -0,0 +1,47 @@
+// Fields can be returned to their initial state. This is useful when
+// reusing a cloned builder as a template.
+
+use builders::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(clone)]
+pub struct Request {
+    url: String,
+    #[builder(def = { String::from("GET") })]
+    method: String,
+    #[builder(optional = true)]
+    body: Option<Vec<u8>>,
+    #[builder(map = "header")]
+    headers: HashMap<String, String>,
+    #[builder(vec = "param")]
+    params: Vec<String>,
+}
+
+fn main() {
+    let template = Request::builder()
+        .url("example.com")
+        .method("POST")
+        .body(vec![1, 2, 3])
+        .header("Host", "example.com")
+        .param("q");
+
+    let req = template.clone()
+        .unset_method()
+        .unset_body()
+        .unset_headers()
+        .unset_params()
+        .build()
+        .unwrap();
+    assert_eq!(req.method, "GET");
+    assert_eq!(req.body, None);
+    assert!(req.headers.is_empty());
+    assert!(req.params.is_empty());
+
+    let mut builder = template.clone();
+    builder.clear_url().clear_body();
+    assert!(!builder.is_url_set());
+    assert!(!builder.is_body_set());
+    assert!(builder.is_headers_set());
+    assert!(builder.build().is_err());
+}
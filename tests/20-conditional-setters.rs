@@ -0,0 +1,43 @@
+// Fields can be set conditionally, either from an Option or from a boolean
+// condition, without breaking the method chain.
+
+use builders::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(def = false)]
+    verbose: bool,
+    #[builder(optional = true)]
+    current_dir: Option<String>,
+}
+
+struct Config {
+    dir: Option<&'static str>,
+    exec: Option<String>,
+}
+
+fn main() {
+    let config = Config { dir: Some(".."), exec: None };
+
+    let command = Command::builder()
+        .executable("cargo")
+        .maybe_executable(config.exec)
+        .maybe_current_dir(config.dir)
+        .verbose_if(config.dir.is_none(), true)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert!(!command.verbose);
+
+    let command = Command::builder()
+        .maybe_executable(Some("rustc"))
+        .current_dir_if(false, "/")
+        .verbose_if(true, true)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "rustc");
+    assert_eq!(command.current_dir, None);
+    assert!(command.verbose);
+}
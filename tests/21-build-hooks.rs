@@ -0,0 +1,37 @@
+// The builder can normalize it's fields before building, and finalize
+// the built value afterwards.
+
+use builders::Builder;
+
+#[derive(Builder)]
+#[builder(pre_build = normalize, post_build = Header::compute_len)]
+pub struct Header {
+    name: String,
+    value: String,
+    #[builder(disabled = true, def = 0)]
+    len: usize,
+}
+
+fn normalize(b: &mut HeaderBuilder) {
+    if let Some(name) = &mut b.name {
+        *name = name.trim().to_lowercase();
+    }
+}
+
+impl Header {
+    fn compute_len(&mut self) {
+        self.len = self.name.len() + self.value.len() + 2;
+    }
+}
+
+fn main() {
+    let header = Header::builder()
+        .name("  Content-Type ")
+        .value("text/html")
+        .build()
+        .unwrap();
+    assert_eq!(header.name, "content-type");
+    assert_eq!(header.len, "content-type: text/html".len());
+
+    assert!(Header::builder().name("Host").build().is_err());
+}
@@ -0,0 +1,41 @@
+// The built value can be directly wrapped in a Box, Rc or Arc.
+
+use builders::Builder;
+use std::sync::Arc;
+use std::thread;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Config {
+    name: String,
+    #[builder(def = 8080u16)]
+    port: u16,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(infallible)]
+pub struct Limits {
+    #[builder(def = 10u32)]
+    max_conns: u32,
+}
+
+fn main() {
+    let config = Config::builder().name("server").build_arc().unwrap();
+    let handle = {
+        let config = Arc::clone(&config);
+        thread::spawn(move || config.port)
+    };
+    assert_eq!(handle.join().unwrap(), 8080);
+
+    let config = Config::builder().name("server").build_rc().unwrap();
+    assert_eq!(config.name, "server");
+
+    let config: Box<Config> = Config::builder().name("server").port(80u16).build_boxed().unwrap();
+    assert_eq!(config.port, 80);
+
+    assert!(Config::builder().build_boxed().is_err());
+
+    let limits: Arc<Limits> = Limits::builder().build_arc();
+    assert_eq!(limits.max_conns, 10);
+    let limits: Box<Limits> = Limits::builder().max_conns(5u32).build_boxed();
+    assert_eq!(limits.max_conns, 5);
+}
@@ -0,0 +1,122 @@
+// The #[builder] attribute generates a builder for a function, where each
+// parameter becomes a field. The function is called with `call()`.
+//
+// For associated functions, the impl block must be marked with #[builder]
+// too.
+
+use builders::builder;
+use std::collections::HashMap;
+
+#[builder]
+fn connect(
+    host: &str,
+    #[builder(def = 80u16)]
+    port: u16,
+    #[builder(optional = true)]
+    user: Option<String>,
+    #[builder(vec = "proto")]
+    protocols: Vec<&'static str>,
+    #[builder(map = "option")]
+    options: HashMap<String, String>,
+) -> String {
+    let mut s = match user {
+        Some(user) => format!("{user}@{host}:{port}"),
+        None => format!("{host}:{port}"),
+    };
+    for p in protocols {
+        s.push(' ');
+        s.push_str(p);
+    }
+    s.push_str(&format!(" {}", options.len()));
+    s
+}
+
+#[builder(infallible)]
+fn sum<T: std::ops::Add<Output = T> + Default>(#[builder(def = T::default())] a: T, #[builder(def = T::default())] b: T) -> T {
+    a + b
+}
+
+#[builder]
+fn first_word(text: &str) -> &str {
+    text.split(' ').next().unwrap_or_default()
+}
+
+#[builder]
+fn parse<T: std::str::FromStr>(s: &str) -> Option<T> {
+    s.parse().ok()
+}
+
+#[builder(clone)]
+fn count<'a>(n: usize) -> &'a str {
+    if n == 1 { "one" } else { "many" }
+}
+
+pub struct Point<T> {
+    x: T,
+    y: T,
+}
+
+pub struct Pool<T> {
+    items: Vec<T>,
+}
+
+#[builder]
+impl<T> Pool<T> {
+    #[builder(debug)]
+    pub fn new(#[builder(def = 8usize)] cap: usize) -> Self {
+        Self { items: Vec::with_capacity(cap) }
+    }
+}
+
+#[builder]
+impl<T: Default> Point<T> {
+    #[builder]
+    pub fn new(#[builder(def = T::default())] x: T, #[builder(def = T::default())] y: T) -> Self {
+        Self { x, y }
+    }
+
+    #[builder(infallible)]
+    pub fn origin(#[builder(optional = true)] offset: Option<T>) -> Self {
+        let x = offset.unwrap_or_default();
+        Self { x, y: T::default() }
+    }
+}
+
+fn main() {
+    let s = connect_builder()
+        .host("example.com")
+        .user("root")
+        .proto("http")
+        .proto("ftp")
+        .option("a", "b")
+        .call()
+        .unwrap();
+    assert_eq!(s, "root@example.com:80 http ftp 1");
+
+    assert_eq!(connect_builder().port(8080u16).call(), Err("host is not set"));
+
+    let n: u8 = sum_builder().a(2u8).b(3u8).call();
+    assert_eq!(n, 5);
+    let n: i32 = sum_builder().b(3).call();
+    assert_eq!(n, 3);
+
+    let word = first_word_builder().text("hello world").call().unwrap();
+    assert_eq!(word, "hello");
+
+    let n: Option<u32> = parse_builder().s("42").call().unwrap();
+    assert_eq!(n, Some(42));
+
+    let builder = count_builder().n(1usize);
+    assert_eq!(builder.clone().call().unwrap(), "one");
+
+    let builder = Pool::<String>::new_builder().cap(16usize);
+    assert_eq!(format!("{builder:?}"), "PoolNewBuilder { cap: Some(16) }");
+    let pool = builder.call();
+    assert!(pool.items.capacity() >= 16);
+
+    let p = Point::new_builder().y(12).call();
+    assert_eq!((p.x, p.y), (0, 12));
+
+    let p: Point<i64> = Point::origin_builder().offset(4).call();
+    assert_eq!((p.x, p.y), (4, 0));
+}
@@ -0,0 +1,41 @@
+// Fields marked as `start` are passed as arguments to the builder()
+// function, so they can't be missing when calling build(), which
+// becomes infallible.
+
+use builders::{builder, Builder};
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(start)]
+    method: String,
+    #[builder(start)]
+    url: String,
+    #[builder(def = 1.1f32)]
+    version: f32,
+    #[builder(vec = "header")]
+    headers: Vec<(String, String)>,
+}
+
+#[builder]
+fn greet(#[builder(start)] name: &str, #[builder(def = "Hello")] greeting: &str) -> String {
+    format!("{greeting}, {name}!")
+}
+
+fn main() {
+    let req = Request::builder("GET", "example.com")
+        .header(("Host".to_string(), "example.com".to_string()))
+        .build();
+    assert_eq!(req.method, "GET");
+    assert_eq!(req.url, "example.com");
+    assert_eq!(req.version, 1.1);
+    assert_eq!(req.headers.len(), 1);
+
+    let builder = Request::builder("GET", "example.com").url("localhost");
+    assert!(builder.is_url_set());
+    assert_eq!(builder.get_method().map(String::as_str), Some("GET"));
+    assert!(builder.missing_fields().is_empty());
+    assert_eq!(builder.build().url, "localhost");
+
+    let s = greet_builder("World").call();
+    assert_eq!(s, "Hello, World!");
+}
@@ -0,0 +1,12 @@
+// Start fields are always set, so they can't be optional or have a default.
+
+use builders::Builder;
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(start)]
+    #[builder(def = { String::from("GET") })]
+    method: String,
+}
+
+fn main() {}
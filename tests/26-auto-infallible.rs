@@ -0,0 +1,47 @@
+// When no field can be missing, build() is infallible without having to
+// mark the builder as such. #[builder(fallible)] keeps returning a Result.
+//
+// Fallible builders can panic on error with build_or_panic() and
+// build_expect(msg).
+
+use builders::Builder;
+use std::panic;
+
+#[derive(Builder)]
+pub struct Limits {
+    #[builder(def = 10u32)]
+    max_conns: u32,
+    #[builder(optional = true)]
+    timeout: Option<u64>,
+}
+
+#[derive(Builder)]
+#[builder(fallible)]
+pub struct Options {
+    #[builder(def = false)]
+    verbose: bool,
+}
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {
+    let limits: Limits = Limits::builder().timeout(12u64).build();
+    assert_eq!(limits.max_conns, 10);
+    assert_eq!(limits.timeout, Some(12));
+
+    let options: Result<Options, &str> = Options::builder().build();
+    assert!(!options.unwrap().verbose);
+    assert!(Options::builder().verbose(true).build_or_panic().verbose);
+
+    let command = Command::builder().executable("ls").build_expect("Invalid command");
+    assert_eq!(command.executable, "ls");
+
+    panic::set_hook(Box::new(|_| {}));
+    let err = panic::catch_unwind(|| Command::builder().build_expect("Invalid command")).unwrap_err();
+    let msg = err.downcast_ref::<String>().unwrap();
+    assert!(msg.starts_with("Invalid command: "));
+    assert!(panic::catch_unwind(|| Command::builder().build_or_panic()).is_err());
+}
@@ -0,0 +1,50 @@
+// Builders implement Default, and can be converted into the built type
+// with From (infallible builders) or TryFrom (fallible builders).
+
+use builders::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Limits {
+    #[builder(def = 10u32)]
+    max_conns: u32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    executable: String,
+    #[builder(vec = "arg")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(start)]
+    url: String,
+}
+
+fn build_default<B: Default + Into<T>, T>() -> T {
+    B::default().into()
+}
+
+fn parse(exec: &str) -> Result<Command, &'static str> {
+    let mut builder = CommandBuilder::default();
+    if !exec.is_empty() {
+        builder.set_executable(exec);
+    }
+    let command = builder.try_into()?;
+    Ok(command)
+}
+
+fn main() {
+    let limits: Limits = build_default::<LimitsBuilder, _>();
+    assert_eq!(limits.max_conns, 10);
+    let limits: Limits = Limits::builder().max_conns(2u32).into();
+    assert_eq!(limits.max_conns, 2);
+
+    assert_eq!(parse("ls").unwrap().executable, "ls");
+    assert!(parse("").is_err());
+    assert!(Command::try_from(Command::builder().arg("-l")).is_err());
+
+    let request: Request = Request::builder("example.com").into();
+    assert_eq!(request.url, "example.com");
+}
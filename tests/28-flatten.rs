@@ -0,0 +1,62 @@
+// A field whose type derives Builder can be flattened. The setters of it's
+// builder are available directly on the outer builder, through the
+// <Type>Flatten trait generated by the inner derive with
+// #[builder(flattenable)].
+
+use builders::Builder;
+
+mod config {
+    use builders::Builder;
+
+    #[derive(Builder, Debug, PartialEq)]
+    #[builder(flattenable)]
+    pub struct NetConfig {
+        pub host: String,
+        #[builder(def = 80u16)]
+        pub port: u16,
+    }
+
+    #[derive(Builder, Debug, PartialEq)]
+    #[builder(flattenable)]
+    pub struct Limits<T: Default> {
+        #[builder(def = T::default())]
+        pub max: T,
+        #[builder(vec = "tag")]
+        pub tags: Vec<String>,
+    }
+}
+
+use config::{LimitsFlatten, NetConfigFlatten};
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Server {
+    name: String,
+    #[builder(flatten)]
+    net: config::NetConfig,
+    #[builder(flatten)]
+    limits: config::Limits<u32>,
+}
+
+fn main() {
+    let server = Server::builder()
+        .name("web")
+        .host("example.com")
+        .port(8080u16)
+        .max(12u32)
+        .tag("prod")
+        .build()
+        .unwrap();
+    assert_eq!(server.name, "web");
+    assert_eq!(server.net.host, "example.com");
+    assert_eq!(server.net.port, 8080);
+    assert_eq!(server.limits.max, 12);
+    assert_eq!(server.limits.tags, ["prod"]);
+
+    let mut builder = Server::builder();
+    builder.set_name("web").set_host("localhost");
+    let server = builder.build().unwrap();
+    assert_eq!(server.net.port, 80);
+    assert_eq!(server.limits.max, 0);
+
+    assert!(Server::builder().name("web").build().is_err());
+}
@@ -0,0 +1,56 @@
+// Fields can be grouped. In an exclusive group at most one of the fields
+// can be set, and in a required group at least one must be set.
+
+use builders::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Client {
+    #[builder(optional = true, group = "auth", exclusive)]
+    token: Option<String>,
+    #[builder(optional = true, group = "auth")]
+    basic_auth: Option<(String, String)>,
+    #[builder(optional = true, group = "target", required)]
+    url: Option<String>,
+    #[builder(optional = true, group = "target")]
+    socket: Option<String>,
+    #[builder(optional = true, group = "mode", exclusive, required)]
+    sync: Option<bool>,
+    #[builder(optional = true, group = "mode")]
+    threads: Option<u32>,
+}
+
+fn main() {
+    let client = Client::builder()
+        .token("abc")
+        .url("example.com")
+        .sync(true)
+        .build()
+        .unwrap();
+    assert_eq!(client.token.as_deref(), Some("abc"));
+
+    let client = Client::builder()
+        .basic_auth(("user".to_string(), "pass".to_string()))
+        .url("example.com")
+        .socket("/run/sock")
+        .threads(4u32)
+        .build()
+        .unwrap();
+    assert!(client.token.is_none());
+
+    let err = Client::builder()
+        .token("abc")
+        .basic_auth(("user".to_string(), "pass".to_string()))
+        .url("example.com")
+        .sync(true)
+        .build()
+        .unwrap_err();
+    assert_eq!(err, "Only one of the fields of group \"auth\" can be set: token, basic_auth");
+
+    let err = Client::builder().sync(true).build().unwrap_err();
+    assert_eq!(err, "One of the fields of group \"target\" must be set: url, socket");
+
+    let err = Client::builder().url("example.com").build().unwrap_err();
+    assert_eq!(err, "One of the fields of group \"mode\" must be set: sync, threads");
+    let err = Client::builder().url("example.com").sync(false).threads(1u32).build().unwrap_err();
+    assert_eq!(err, "Only one of the fields of group \"mode\" can be set: sync, threads");
+}
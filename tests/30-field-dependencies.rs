@@ -0,0 +1,41 @@
+// A field can require another one to be set, or be required only when
+// another field is set.
+
+use builders::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    host: String,
+    #[builder(optional = true)]
+    tls: Option<bool>,
+    #[builder(optional = true, required_if = "tls")]
+    cert: Option<String>,
+    #[builder(optional = true, requires = "cert")]
+    key_password: Option<String>,
+    #[builder(vec = "ca", requires = "tls")]
+    cas: Vec<String>,
+}
+
+fn main() {
+    let server = Server::builder().host("localhost").build().unwrap();
+    assert!(server.cert.is_none());
+
+    let server = Server::builder()
+        .host("localhost")
+        .tls(true)
+        .cert("cert.pem")
+        .key_password("1234")
+        .ca("ca.pem")
+        .build()
+        .unwrap();
+    assert_eq!(server.cas, ["ca.pem"]);
+
+    let err = Server::builder().host("localhost").tls(true).build().unwrap_err();
+    assert_eq!(err, "cert is required when tls is set");
+
+    let err = Server::builder().host("localhost").key_password("1234").build().unwrap_err();
+    assert_eq!(err, "key_password requires cert to be set");
+
+    let err = Server::builder().host("localhost").cert("cert.pem").ca("ca.pem").build().unwrap_err();
+    assert_eq!(err, "cas requires tls to be set");
+}
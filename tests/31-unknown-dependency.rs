@@ -0,0 +1,13 @@
+// Fields referenced by requires and required_if must exist.
+
+use builders::Builder;
+
+#[derive(Builder)]
+pub struct Server {
+    #[builder(optional = true, requires = "tsl")]
+    cert: Option<String>,
+    #[builder(optional = true)]
+    tls: Option<bool>,
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+// Numeric fields can be restricted to a range of values, and strings and
+// collections to a range of lengths.
+
+use builders::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    #[builder(len = 1..=16)]
+    name: String,
+    #[builder(range = 1..=65535, def = 80u16)]
+    port: u16,
+    #[builder(optional = true, range = 0.0..1.0)]
+    ratio: Option<f32>,
+    #[builder(vec = "tag", len = ..4)]
+    tags: Vec<String>,
+    #[builder(map = "header", len = 1..)]
+    headers: HashMap<String, String>,
+}
+
+fn main() {
+    let server = Server::builder()
+        .name("web")
+        .ratio(0.5f32)
+        .tag("a")
+        .header("Host", "example.com")
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 80);
+
+    let err = Server::builder().name("").header("Host", "a").build().unwrap_err();
+    assert_eq!(err, "name length must be in the range 1..=16");
+
+    let err = Server::builder().name("web").port(0u16).header("Host", "a").build().unwrap_err();
+    assert_eq!(err, "port value must be in the range 1..=65535");
+
+    let err = Server::builder().name("web").ratio(1.5f32).header("Host", "a").build().unwrap_err();
+    assert_eq!(err, "ratio value must be in the range 0.0..1.0");
+
+    let err = Server::builder().name("web").tag("a").tag("b").tag("c").tag("d")
+                               .header("Host", "a").build().unwrap_err();
+    assert_eq!(err, "tags length must be in the range ..4");
+
+    let err = Server::builder().name("web").build().unwrap_err();
+    assert_eq!(err, "headers length must be in the range 1..");
+}
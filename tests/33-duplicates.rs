@@ -0,0 +1,61 @@
+// vec and map fields can decide what happens when the same entry
+// is inserted twice. By default, maps replace the old value and
+// vecs keep both entries.
+
+use builders::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder, Debug)]
+pub struct Request {
+    #[builder(map = "header", unique)]
+    headers: HashMap<String, String>,
+    #[builder(map = "param", on_duplicate = "keep_first")]
+    params: HashMap<String, String>,
+    #[builder(vec = "tag", on_duplicate = "keep_first")]
+    tags: Vec<String>,
+    #[builder(vec = "flag", on_duplicate = "replace")]
+    flags: Vec<String>,
+    #[builder(vec = "id", on_duplicate = "error")]
+    ids: Vec<u32>,
+}
+
+fn main() {
+    let req = Request::builder()
+        .header("Host", "example.com")
+        .param("q", "first")
+        .param("q", "second")
+        .tag("a")
+        .tag("a")
+        .tag("b")
+        .flag("x")
+        .flag("x")
+        .id(1u32)
+        .build()
+        .unwrap();
+    assert_eq!(req.params["q"], "first");
+    assert_eq!(req.tags, ["a", "b"]);
+    assert_eq!(req.flags, ["x"]);
+
+    let err = Request::builder()
+        .header("Host", "a")
+        .header("Host", "b")
+        .build()
+        .unwrap_err();
+    assert_eq!(err, "Duplicate entry in headers");
+
+    let err = Request::builder()
+        .id(1u32)
+        .id(1u32)
+        .build()
+        .unwrap_err();
+    assert_eq!(err, "Duplicate entry in ids");
+
+    let req = Request::builder()
+        .header("Host", "a")
+        .header("Host", "b")
+        .unset_headers()
+        .header("Host", "c")
+        .build()
+        .unwrap();
+    assert_eq!(req.headers["Host"], "c");
+}
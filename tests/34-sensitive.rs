@@ -0,0 +1,30 @@
+// Sensitive fields are printed as <redacted> by the Debug impls
+// generated with the debug and debug_target attributes.
+
+use builders::Builder;
+
+#[derive(Builder)]
+#[builder(debug, debug_target)]
+pub struct Credentials {
+    user: String,
+    #[builder(sensitive)]
+    password: String,
+    #[builder(sensitive, optional = true)]
+    api_key: Option<String>,
+}
+
+fn main() {
+    let builder = Credentials::builder()
+        .user("admin")
+        .password("hunter2");
+    let debug = format!("{builder:?}");
+    assert_eq!(debug, r#"CredentialsBuilder { user: Some("admin"), password: <redacted>, api_key: <redacted> }"#);
+
+    let creds = builder.build().unwrap();
+    let debug = format!("{creds:?}");
+    assert_eq!(debug, r#"Credentials { user: "admin", password: <redacted>, api_key: <redacted> }"#);
+    assert!(!debug.contains("hunter2"));
+
+    let err = Credentials::builder().user("admin").build().unwrap_err();
+    assert!(!err.contains("hunter2"));
+}
@@ -0,0 +1,24 @@
+// Derives and attributes can be forwarded to the builder struct.
+
+use builders::Builder;
+use std::collections::HashSet;
+
+#[derive(Builder, Debug)]
+#[builder(derive(Debug, PartialEq, Eq, Hash), attr(doc = "Builds a point"))]
+#[builder(attr(allow(dead_code)))]
+pub struct Point {
+    x: i32,
+    #[builder(def = 0, field_attr(doc = "The y coordinate"))]
+    y: i32,
+}
+
+fn main() {
+    let a = Point::builder().x(1);
+    let b = Point::builder().x(1);
+    assert_eq!(a, b);
+    assert_eq!(format!("{a:?}"), "PointBuilder { x: Some(1), y: None }");
+
+    let mut set = HashSet::new();
+    set.insert(a);
+    assert!(!set.insert(b));
+}
@@ -0,0 +1,11 @@
+use builders::Builder;
+
+#[derive(Builder)]
+#[builder(derive(Debug))]
+pub struct Login {
+    user: String,
+    #[builder(sensitive)]
+    password: String,
+}
+
+fn main() {}
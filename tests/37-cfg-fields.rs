@@ -0,0 +1,39 @@
+// #[cfg] attributes on fields are copied onto everything generated
+// for them, so the field can be compiled out.
+
+use builders::*;
+
+#[derive(Builder, Getters, Setters, Constructor, Debug)]
+#[builder(clone, debug)]
+pub struct Config {
+    name: String,
+    #[cfg(any())]
+    #[builder(group = "source", exclusive)]
+    missing: DoesNotExist,
+    #[cfg(all())]
+    #[builder(def = 1u8, range = 1..=10)]
+    level: u8,
+    #[cfg_attr(all(), cfg(any()))]
+    #[builder(vec = "tag", unique)]
+    tags: Vec<NotAType>,
+}
+
+#[builder]
+fn connect(host: &str, #[cfg(any())] proxy: DoesNotExist, #[builder(def = 80u16)] port: u16) -> String {
+    format!("{host}:{port}")
+}
+
+fn main() {
+    let config = Config::builder()
+        .name("test")
+        .level(3u8)
+        .clone()
+        .build()
+        .unwrap();
+    assert_eq!(*config.get_level(), 3);
+
+    let config = Config::new("test", 2u8);
+    assert_eq!(config.get_name(), "test");
+
+    assert_eq!(connect_builder().host("localhost").call().unwrap(), "localhost:80");
+}
@@ -0,0 +1,41 @@
+// Fields named with raw identifiers get setters without the r# prefix,
+// unless the generated name is a keyword itself.
+
+use builders::*;
+
+#[derive(Builder, Getters, Setters, Debug)]
+#[builder(debug, clone)]
+pub struct Token {
+    r#type: String,
+    #[builder(vec = "match")]
+    r#matches: Vec<String>,
+    #[builder(optional = true)]
+    r#loop: Option<u32>,
+}
+
+#[builder]
+fn r#move(r#type: u8, r#ref: &str) -> String {
+    format!("{type}{ref}", type = r#type, ref = r#ref)
+}
+
+fn main() {
+    let builder = Token::builder()
+        .r#type("ident")
+        .r#match("a")
+        .r#loop(3u32)
+        .clone();
+    assert!(builder.is_type_set());
+    assert_eq!(builder.get_type().unwrap(), "ident");
+    assert_eq!(builder.missing_fields(), Vec::<&str>::new());
+
+    let mut token = builder.build().unwrap();
+    assert_eq!(token.get_type(), "ident");
+    assert_eq!(token.get_matches(), &["a"]);
+    token.set_type("literal");
+    assert_eq!(token.r#type, "literal");
+
+    let err = Token::builder().build().unwrap_err();
+    assert!(!err.contains("r#"));
+
+    assert_eq!(move_builder().r#type(1u8).r#ref("x").call().unwrap(), "1x");
+}
@@ -0,0 +1,9 @@
+use builders::Builder;
+
+#[derive(Builder)]
+pub struct Job {
+    name: String,
+    build: u32,
+}
+
+fn main() {}
@@ -0,0 +1,46 @@
+//! Every public item generated by the derives is documented, either with
+//! the doc comments of the field or with a default one.
+#![deny(missing_docs)]
+
+use builders::*;
+use std::collections::HashMap;
+
+/// A request
+#[derive(Builder, Getters, Setters, Constructor)]
+#[builder(clone, flattenable)]
+pub struct Request {
+    /// The url of the request
+    url: String,
+    /// Extra headers
+    #[builder(map = "header")]
+    headers: HashMap<String, String>,
+    #[builder(vec = "param")]
+    params: Vec<String>,
+    #[builder(optional = true)]
+    body: Option<String>,
+    #[builder(def = 30u32, range = 1..=60)]
+    timeout: u32,
+}
+
+/// A connection
+#[derive(Builder)]
+pub struct Connection {
+    #[builder(start = true)]
+    host: String,
+    #[builder(flatten = true)]
+    request: Request,
+}
+
+/// Sends a request
+#[builder]
+pub fn send(url: &str, #[builder(def = 1u8)] retries: u8) -> String {
+    format!("{url} {retries}")
+}
+
+fn main() {
+    let request = Request::builder().url("/").build().unwrap();
+    assert_eq!(request.get_timeout(), &30);
+    let conn = Connection::builder("localhost").url("/").build().unwrap();
+    assert_eq!(conn.host, "localhost");
+    assert_eq!(send_builder().url("/").call().unwrap(), "/ 1");
+}
@@ -0,0 +1,12 @@
+#![deny(unused_must_use)]
+
+use builders::Builder;
+
+#[derive(Builder)]
+pub struct Request {
+    url: String,
+}
+
+fn main() {
+    Request::builder().url("/");
+}
@@ -0,0 +1,27 @@
+// The inline attribute is forwarded to every generated method.
+
+use builders::Builder;
+
+#[derive(Builder)]
+#[builder(inline = "always")]
+pub struct Point {
+    x: i32,
+    #[builder(def = 0)]
+    y: i32,
+}
+
+#[derive(Builder)]
+#[builder(inline = "never")]
+pub struct Size {
+    #[builder(vec = "dim")]
+    dims: Vec<u32>,
+}
+
+fn main() {
+    let p = Point::builder().x(1).build().unwrap();
+    assert_eq!((p.x, p.y), (1, 0));
+
+    let mut builder = Size::builder();
+    builder.set_dim(2u32);
+    assert_eq!(builder.build().dims, [2]);
+}
@@ -0,0 +1,45 @@
+// const builders can be used to initialize statics and constants.
+
+use builders::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(const)]
+pub struct Config {
+    #[builder(start = true)]
+    name: &'static str,
+    port: u16,
+    #[builder(def = 4)]
+    threads: u8,
+    #[builder(optional = true, requires = "port")]
+    timeout: Option<u32>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(const)]
+pub struct Limits {
+    #[builder(def = 10)]
+    max: u32,
+    #[builder(optional = true)]
+    min: Option<u32>,
+}
+
+static CONFIG: Config = match Config::builder("server").port(8080).timeout(30).build() {
+    Ok(config) => config,
+    Err(_) => panic!("invalid config"),
+};
+
+const LIMITS: Limits = Limits::builder().max(3).build();
+
+const MISSING: Result<Config, &str> = Config::builder("server").build();
+
+fn main() {
+    assert_eq!(CONFIG.name, "server");
+    assert_eq!(CONFIG.port, 8080);
+    assert_eq!(CONFIG.threads, 4);
+    assert_eq!(CONFIG.timeout, Some(30));
+
+    assert_eq!(LIMITS.max, 3);
+    assert_eq!(LIMITS.min, None);
+
+    assert_eq!(MISSING.unwrap_err(), "port is not set");
+}
@@ -0,0 +1,10 @@
+use builders::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Args {
+    #[builder(vec = "arg")]
+    args: Vec<&'static str>,
+}
+
+fn main() {}
@@ -0,0 +1,38 @@
+// Path segments, function calls and macros in a default value are not
+// field references, even when they share the name of a field. A field is
+// not in scope inside it's own default value either.
+
+use std::path::PathBuf;
+use builders::Builder;
+
+fn timeout() -> u32 { 5 }
+
+macro_rules! retries {
+    () => { 3 };
+}
+
+#[derive(Builder)]
+pub struct Project {
+    #[builder(def = { std::path::PathBuf::from(".") })]
+    path: PathBuf,
+    #[builder(def = Vec::new())]
+    new: Vec<u8>,
+    #[builder(def = { path.join("target") })]
+    target: PathBuf,
+    #[builder(def = { timeout() })]
+    timeout: u32,
+    #[builder(def = { retries!() + timeout })]
+    retries: u32,
+    #[builder(def = { retries != 0 })]
+    enabled: bool,
+}
+
+fn main() {
+    let project = Project::builder().build();
+    assert_eq!(project.path, PathBuf::from("."));
+    assert!(project.new.is_empty());
+    assert_eq!(project.target, PathBuf::from("./target"));
+    assert_eq!(project.timeout, 5);
+    assert_eq!(project.retries, 8);
+    assert!(project.enabled);
+}
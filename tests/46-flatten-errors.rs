@@ -0,0 +1,41 @@
+// The missing fields and the errors of a flattened builder are reported
+// by the outer builder, prefixed with the name of the flattened field.
+// A setter of the outer builder shadows the one of the inner builder
+// with the same name.
+
+use builders::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(flattenable)]
+pub struct Net {
+    name: String,
+    #[builder(range = 1..)]
+    port: u16,
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    name: String,
+    #[builder(flatten)]
+    net: Net,
+}
+
+fn main() {
+    let builder = Server::builder().port(1u16);
+    assert_eq!(builder.missing_fields(), ["name", "net.name"]);
+
+    let builder = Server::builder().name("a").port(1u16);
+    assert_eq!(builder.missing_fields(), ["net.name"]);
+    assert_eq!(builder.build().unwrap_err(), "net: name is not set");
+
+    let mut builder = Server::builder().name("a").port(0u16);
+    NetFlatten::set_name(&mut builder, "eth0");
+    assert!(builder.missing_fields().is_empty());
+    assert_eq!(builder.build().unwrap_err(), "net: port value must be in the range 1..");
+
+    let builder = Server::builder().name("a").port(80u16);
+    let server = NetFlatten::name(builder, "eth0").build_expect("flattened");
+    assert_eq!(server.name, "a");
+    assert_eq!(server.net.name, "eth0");
+    assert_eq!(server.net.port, 80);
+}
@@ -0,0 +1,24 @@
+// The Debug and Clone impls of generic builders keep the where clause of
+// the struct, and add their own bounds to it.
+
+use builders::Builder;
+
+#[derive(Builder)]
+#[builder(debug, debug_target, clone)]
+pub struct Wrapper<T>
+where
+    T: Default,
+{
+    #[builder(def = T::default())]
+    value: T,
+    #[builder(sensitive)]
+    secret: String,
+}
+
+fn main() {
+    let builder = Wrapper::<u8>::builder().value(3u8).secret("hunter2");
+    assert_eq!(format!("{:?}", builder.clone()), "WrapperBuilder { value: Some(3), secret: <redacted> }");
+
+    let wrapper = builder.build().unwrap();
+    assert_eq!(format!("{wrapper:?}"), "Wrapper { value: 3, secret: <redacted> }");
+}
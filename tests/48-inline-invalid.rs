@@ -0,0 +1,10 @@
+use builders::Builder;
+
+#[derive(Builder)]
+#[builder(inline = "sometimes")]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn main() {}
@@ -0,0 +1,58 @@
+// const builders work with fields that have destructors. Since those
+// can't run in const fns, the builder never drops a value.
+
+use builders::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(const)]
+pub struct User {
+    #[builder(start = true)]
+    id: String,
+    name: String,
+    #[builder(def = Vec::new())]
+    roles: Vec<String>,
+    #[builder(optional = true, requires = "name")]
+    email: Option<String>,
+}
+
+const EMPTY: Result<User, &str> = User::builder(String::new()).build();
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Tags {
+    #[builder(def = Vec::new())]
+    tags: Vec<String>,
+    #[builder(optional = true)]
+    label: Option<String>,
+}
+
+const TAGS: Tags = Tags::builder().label(String::new()).build();
+
+fn main() {
+    assert_eq!(EMPTY.unwrap_err(), "name is not set");
+    assert!(TAGS.tags.is_empty());
+    assert_eq!(TAGS.label.as_deref(), Some(""));
+
+    let user = User::builder("1".to_owned())
+        .name("first".to_owned())
+        .name("second".to_owned())
+        .maybe_email(Some("a@b.c".to_owned()))
+        .roles_if(false, vec!["admin".to_owned()])
+        .id("2".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(user.id, "2");
+    assert_eq!(user.name, "second");
+    assert_eq!(user.email.as_deref(), Some("a@b.c"));
+    assert!(user.roles.is_empty());
+
+    let err = User::builder("3".to_owned())
+        .email("a@b.c".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(err, "email requires name to be set");
+
+    let builder = User::builder("4".to_owned());
+    assert_eq!(builder.get_id().map(String::as_str), Some("4"));
+    assert_eq!(builder.missing_fields(), ["name"]);
+}
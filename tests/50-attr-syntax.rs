@@ -0,0 +1,23 @@
+// The values of the builder attributes are parsed as expressions, so they
+// can contain commas inside of generic arguments.
+
+use std::collections::HashMap;
+use builders::Builder;
+
+#[derive(Builder)]
+#[builder(clone, inline = "always")]
+pub struct Cache {
+    #[builder(def = HashMap::<String, u32>::new(), sensitive)]
+    entries: HashMap<String, u32>,
+    #[builder(def = <(u8, u8)>::default(), field_attr(allow(dead_code)))]
+    pair: (u8, u8),
+    #[builder(range = 1..=u8::MAX, def = 1)]
+    weight: u8,
+}
+
+fn main() {
+    let cache = Cache::builder().build().unwrap();
+    assert!(cache.entries.is_empty());
+    assert_eq!(cache.pair, (0, 0));
+    assert_eq!(cache.weight, 1);
+}
@@ -0,0 +1,25 @@
+use builders::Builder;
+
+#[derive(Builder)]
+pub struct Flags {
+    #[builder(optional = yes)]
+    verbose: Option<bool>,
+}
+
+#[derive(Builder)]
+pub struct Paths {
+    #[builder(vec = "path" "other")]
+    paths: Vec<String>,
+}
+
+fn default_name(_: &UserBuilder) -> String {
+    String::new()
+}
+
+#[derive(Builder)]
+pub struct User {
+    #[builder(optional = true, default_with = default_name)]
+    name: Option<String>,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+//! Builds the no-alloc crate, which is outside the workspace so that
+//! the alloc feature of builders isn't enabled by the other members.
+
+use std::process::Command;
+
+#[test]
+fn no_alloc() {
+    let dir = env!("CARGO_MANIFEST_DIR");
+    let status = Command::new(env!("CARGO"))
+        .arg("build")
+        .arg("--manifest-path").arg(format!("{dir}/no-alloc/Cargo.toml"))
+        .arg("--target-dir").arg(format!("{dir}/target/no-alloc"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
@@ -14,6 +14,43 @@ fn tests() {
     t.pass("tests/11-prefix.rs");
     t.pass("tests/12-generics.rs");
     t.pass("tests/13-default.rs");
+    t.pass("tests/15-default-order.rs");
+    t.compile_fail("tests/16-default-cycle.rs");
+    t.pass("tests/17-default-with.rs");
+    t.pass("tests/18-introspection.rs");
+    t.pass("tests/19-unset.rs");
+    t.pass("tests/20-conditional-setters.rs");
+    t.pass("tests/21-build-hooks.rs");
+    t.pass("tests/22-build-pointers.rs");
+    t.pass("tests/23-fn-builder.rs");
+    t.pass("tests/24-start-fields.rs");
+    t.compile_fail("tests/25-start-optional.rs");
+    t.pass("tests/26-auto-infallible.rs");
+    t.pass("tests/27-conversions.rs");
+    t.pass("tests/28-flatten.rs");
+    t.pass("tests/29-groups.rs");
+    t.pass("tests/30-field-dependencies.rs");
+    t.compile_fail("tests/31-unknown-dependency.rs");
+    t.pass("tests/32-range-len.rs");
+    t.pass("tests/33-duplicates.rs");
+    t.pass("tests/34-sensitive.rs");
+    t.pass("tests/35-forward-attrs.rs");
+    t.compile_fail("tests/36-derive-debug-sensitive.rs");
+    t.pass("tests/37-cfg-fields.rs");
+    t.pass("tests/38-raw-identifiers.rs");
+    t.compile_fail("tests/39-reserved-names.rs");
+    t.pass("tests/40-docs.rs");
+    t.compile_fail("tests/41-must-use.rs");
+    t.pass("tests/42-inline.rs");
+    t.pass("tests/43-const-builder.rs");
+    t.compile_fail("tests/44-const-vec.rs");
+    t.pass("tests/45-default-paths.rs");
+    t.pass("tests/46-flatten-errors.rs");
+    t.pass("tests/47-debug-where.rs");
+    t.compile_fail("tests/48-inline-invalid.rs");
+    t.pass("tests/49-const-drop.rs");
+    t.pass("tests/50-attr-syntax.rs");
+    t.compile_fail("tests/51-attr-errors.rs");
     t.pass("tests/http.rs");
     t.pass("tests/constructor.rs");
     t.pass("tests/infallible.rs");